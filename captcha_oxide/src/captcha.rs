@@ -12,6 +12,17 @@ pub trait Captcha: Serialize + Send + Sync + Debug {
 
     fn get_timeout(&self) -> Duration;
 
+    /// The 2captcha name of the type of this task, e.g. `ImageToTextTask`,
+    /// which is sent as its `type`
+    fn get_task_type(&self) -> &'static str;
+
+    /// How long to wait between two requests for this task's result while
+    /// it is still being processed. Image tasks, which workers answer within
+    /// seconds, are polled every 3 seconds, and every other task every 5
+    fn get_poll_interval(&self) -> Duration {
+        Duration::from_secs(5)
+    }
+
     #[must_use]
     fn builder() -> Self::Builder {
         Self::Builder::default()
@@ -20,3 +31,50 @@ pub trait Captcha: Serialize + Send + Sync + Debug {
 
 #[derive(Serialize, PartialEq, Eq, Debug)]
 pub struct Empty;
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use url::Url;
+
+    use crate::{
+        captcha::types::{grid_captcha::GridCaptcha, recaptcha::v2::RecaptchaV2},
+        mock, Captcha,
+    };
+
+    #[test]
+    fn poll_intervals_by_task_type() {
+        let image = mock::captcha();
+        let grid = GridCaptcha::builder().body(mock::IMAGE).build();
+        let recaptcha = RecaptchaV2::builder()
+            .website_url(Url::parse("https://example.com").unwrap())
+            .website_key("SITE_KEY")
+            .build();
+
+        assert_eq!(image.get_poll_interval(), Duration::from_secs(3));
+        assert_eq!(grid.get_poll_interval(), Duration::from_secs(3));
+        assert_eq!(recaptcha.get_poll_interval(), Duration::from_secs(5));
+    }
+
+    fn serialized_type<T: Captcha>(task: &T) -> String {
+        serde_json::to_value(task).unwrap()["type"]
+            .as_str()
+            .unwrap()
+            .to_owned()
+    }
+
+    #[test]
+    fn task_types_match_the_serialized_type() {
+        let grid = GridCaptcha::builder().body(mock::IMAGE).build();
+        let recaptcha = RecaptchaV2::builder()
+            .website_url(Url::parse("https://example.com").unwrap())
+            .website_key("SITE_KEY")
+            .build();
+
+        assert_eq!(mock::captcha().get_task_type(), "ImageToTextTask");
+        assert_eq!(grid.get_task_type(), serialized_type(&grid));
+        assert_eq!(recaptcha.get_task_type(), "RecaptchaV2TaskProxyless");
+        assert_eq!(recaptcha.get_task_type(), serialized_type(&recaptcha));
+    }
+}
@@ -1,10 +1,15 @@
-use std::{borrow::Cow, net::IpAddr};
+use std::{marker::PhantomData, net::IpAddr};
 
 use chrono::{serde::ts_seconds, DateTime, Utc};
 use serde::Deserialize;
 
 use super::Captcha;
+use crate::{
+    captcha_solver::{SolveMetrics, TaskId},
+    Money,
+};
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Status {
     Correct,
     Incorrect,
@@ -20,16 +25,20 @@ where
     /// The task id is not returned by 2captcha, instead it
     /// is manually added to the struct in order to allow the
     /// use of the [`crate::CaptchaSolver::report`] method without
-    /// allowing the user to see (or more importantly, change)
-    /// the task id
-    #[serde(default = "Default::default")]
-    pub(crate) task_id: u64,
+    /// allowing the user to change the task id. Use
+    /// [`Solution::task_id`] to read it
+    #[serde(skip_deserializing, default = "TaskId::unknown")]
+    pub(crate) task_id: TaskId,
 
     /// The actual solution to the captcha
     pub solution: T::Solution,
 
-    /// The task price charged from your balance
-    pub cost: Cow<'a, str>,
+    /// The task price charged from your balance. It is unknown for tasks
+    /// solved through [`crate::captcha_solver::Protocol::V1`] when
+    /// `res.php` does not return their price, and such tasks are not
+    /// counted towards the spend limits, the budget or the stats
+    #[serde(default)]
+    pub cost: Option<Money>,
 
     /// Timestamp indicating the moment task was submitted
     #[serde(with = "ts_seconds")]
@@ -44,4 +53,26 @@ where
 
     /// The IP address that submitted the task request
     pub ip: IpAddr,
+
+    /// How the solve went on this side: how long it took, how many times
+    /// the task was polled and which retries happened
+    #[serde(skip)]
+    pub metrics: SolveMetrics,
+
+    /// Keeps the lifetime parameter, which the cost used to borrow
+    #[serde(skip)]
+    pub(crate) lifetime: PhantomData<&'a ()>,
+}
+
+impl<T> Solution<'_, T>
+where
+    T: Captcha,
+{
+    /// The id of the task this solution belongs to. It can be stored and
+    /// used to report the solution with [`crate::CaptchaSolver::report_by_id`]
+    /// after this value is gone
+    #[must_use]
+    pub const fn task_id(&self) -> TaskId {
+        self.task_id
+    }
 }
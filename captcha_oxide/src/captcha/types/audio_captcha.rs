@@ -19,7 +19,12 @@ use crate::captcha::captcha;
 ///     .language(Language::Portuguese)
 ///     .build();
 /// ```
-#[captcha(crate = "crate", timeout = 5, solution = "AudioCaptchaSolution<'a>")]
+#[captcha(
+    crate = "crate",
+    timeout = 5,
+    poll_interval = 3,
+    solution = "AudioCaptchaSolution<'a>"
+)]
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase", tag = "type", rename = "AudioTask")]
 pub struct AudioCaptcha<'a> {
@@ -47,13 +47,21 @@ pub struct BoundingBoxCaptcha<'a> {
     img_instructions: Option<&'a str>,
 }
 
-impl<'a> Captcha for BoundingBoxCaptcha<'a> {
+impl Captcha for BoundingBoxCaptcha<'_> {
     type Solution = BoundingBoxCaptchaSolution;
     type Builder = BoundingBoxCaptchaBuilder<MissingBody, MissingComment, MissingImgInstructions>;
 
     fn get_timeout(&self) -> Duration {
         Duration::from_secs(5)
     }
+
+    fn get_task_type(&self) -> &'static str {
+        "DrawAroundTask"
+    }
+
+    fn get_poll_interval(&self) -> Duration {
+        Duration::from_secs(3)
+    }
 }
 
 #[derive(Debug, Deserialize, PartialEq, Eq)]
@@ -24,7 +24,6 @@ impl BoundingBoxCaptchaBuilder<MissingBody, MissingComment, MissingImgInstructio
 }
 
 impl Default for BoundingBoxCaptchaBuilder<MissingBody, MissingComment, MissingImgInstructions> {
-    #[must_use]
     fn default() -> Self {
         Self::new()
     }
@@ -46,7 +46,6 @@ pub struct CapyCaptcha<'a> {
 
 #[derive(Deserialize, Debug, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
-
 pub struct CapyCaptchaSolution<'a> {
     #[serde(rename = "captchakey")]
     pub captcha_key: Cow<'a, str>,
@@ -3,7 +3,8 @@ use serde::{Deserialize, Serialize};
 use crate::captcha::captcha;
 
 /// This method can be used to bypass tasks where you need to click
-/// on some points of an image. \
+/// on some points of an image.
+///
 /// It can be also used for cases where you need to calculate a distance
 /// between points. \
 /// \
@@ -25,7 +26,12 @@ use crate::captcha::captcha;
 ///     .comment("Click the green apple")
 ///     .build();
 /// ```
-#[captcha(crate = "crate", timeout = 5, solution = "CoordinatesCaptchaSolution")]
+#[captcha(
+    crate = "crate",
+    timeout = 5,
+    poll_interval = 3,
+    solution = "CoordinatesCaptchaSolution"
+)]
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase", tag = "type", rename = "CoordinatesTask")]
 pub struct CoordinatesCaptcha<'a> {
@@ -46,13 +46,21 @@ pub struct DrawAroundCaptcha<'a> {
     img_instructions: Option<&'a str>,
 }
 
-impl<'a> Captcha for DrawAroundCaptcha<'a> {
+impl Captcha for DrawAroundCaptcha<'_> {
     type Solution = DrawAroundCaptchaSolution;
     type Builder = DrawAroundCaptchaBuilder<MissingBody, MissingComment, MissingImgInstructions>;
 
     fn get_timeout(&self) -> Duration {
         Duration::from_secs(5)
     }
+
+    fn get_task_type(&self) -> &'static str {
+        "DrawAroundTask"
+    }
+
+    fn get_poll_interval(&self) -> Duration {
+        Duration::from_secs(3)
+    }
 }
 
 #[derive(Debug, Deserialize, PartialEq, Eq)]
@@ -24,7 +24,6 @@ impl DrawAroundCaptchaBuilder<MissingBody, MissingComment, MissingImgInstruction
 }
 
 impl Default for DrawAroundCaptchaBuilder<MissingBody, MissingComment, MissingImgInstructions> {
-    #[must_use]
     fn default() -> Self {
         Self::new()
     }
@@ -42,8 +42,9 @@ where
     init_parameters: InitParameters<'a, T>,
 }
 
+/// Unlike the rest of the task, these are sent in snake case, as
+/// `initGeetest` takes them
 #[derive(Debug, Serialize)]
-#[serde(rename_all = "camelCase")]
 pub struct InitParameters<'a, T> {
     captcha_id: &'a str,
 
@@ -61,7 +62,7 @@ pub struct GeeTestV4Solution<'a> {
     pub captcha_output: Cow<'a, str>,
 }
 
-impl<'a, T> GeeTestV4<'a, T>
+impl<T> GeeTestV4<'_, T>
 where
     T: Serialize + Debug + Send + Sync,
 {
@@ -92,7 +93,7 @@ where
 
 // Geetest v4 has a few quirks that require custom serialization, most notably,
 // the version field, which must always be equal to 4
-impl<'a, T> Serialize for GeeTestV4<'a, T>
+impl<T> Serialize for GeeTestV4<'_, T>
 where
     T: Serialize + Debug + Send + Sync,
 {
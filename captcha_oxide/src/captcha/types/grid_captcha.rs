@@ -54,11 +54,19 @@ pub struct GridCaptchaSolution {
     pub click: Box<[u8]>,
 }
 
-impl<'a> Captcha for GridCaptcha<'a> {
+impl Captcha for GridCaptcha<'_> {
     type Solution = GridCaptchaSolution;
     type Builder = GridCaptchaBuilder<MissingBody, MissingComment, MissingImgInstructions>;
 
     fn get_timeout(&self) -> Duration {
         Duration::from_secs(5)
     }
+
+    fn get_task_type(&self) -> &'static str {
+        "GridTask"
+    }
+
+    fn get_poll_interval(&self) -> Duration {
+        Duration::from_secs(3)
+    }
 }
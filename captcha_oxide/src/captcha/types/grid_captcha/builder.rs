@@ -28,7 +28,6 @@ impl GridCaptchaBuilder<MissingBody, MissingComment, MissingImgInstructions> {
 }
 
 impl Default for GridCaptchaBuilder<MissingBody, MissingComment, MissingImgInstructions> {
-    #[must_use]
     fn default() -> Self {
         Self::new()
     }
@@ -62,19 +62,16 @@ pub struct HCaptchaSolution<'a> {
 
 #[cfg(test)]
 mod test {
-    use std::env;
-
     use url::Url;
 
     use crate::{
         captcha::types::h_captcha::{proxy::ProxyTask, HCaptcha},
-        Captcha, CaptchaSolver, Result,
+        mock, Captcha, Result,
     };
 
     #[tokio::test]
     async fn h_captcha() -> Result<()> {
-        dotenv::dotenv().unwrap();
-        let solver = CaptchaSolver::new(env::var("API_KEY").unwrap());
+        let (solver, replay) = mock::fixture("h_captcha");
 
         let captcha = <HCaptcha>::builder()
             .website_url(Url::parse("https://2captcha.com/demo/hcaptcha")?)
@@ -82,9 +79,10 @@ mod test {
             .proxy(ProxyTask::ProxyLess)
             .build();
 
-        let solution = solver.solve(&captcha).await?.solution.token;
+        let solution = mock::solved(solver.solve(&captcha).await)?.solution.token;
 
-        assert!(!solution.is_empty());
+        assert!(replay.is_finished());
+        assert_eq!(solution, "P1_eyJ0eXAiOiJKV...");
 
         Ok(())
     }
@@ -63,16 +63,13 @@ pub struct KeyCaptchaSolution<'a> {
 
 #[cfg(test)]
 mod test {
-    use std::env;
-
     use url::Url;
 
-    use crate::{captcha::types::key_captcha::KeyCaptcha, Captcha, CaptchaSolver, Error};
+    use crate::{captcha::types::key_captcha::KeyCaptcha, mock, Captcha, Error};
 
     #[tokio::test]
     async fn key_captcha() -> Result<(), Error> {
-        dotenv::dotenv().unwrap();
-        let solver = CaptchaSolver::new(env::var("API_KEY").unwrap());
+        let (solver, replay) = mock::fixture("key_captcha");
 
         let captcha = KeyCaptcha::builder()
             .website_url(Url::parse("https://2captcha.com/demo/keycaptcha")?)
@@ -82,9 +79,10 @@ mod test {
             .web_server_sign2("104ac902450db8362ce5fc11e841ee47")
             .build();
 
-        let solution = solver.solve(&captcha).await?.solution;
+        let solution = mock::solved(solver.solve(&captcha).await)?.solution;
 
-        assert_ne!(solution.token, "");
+        assert!(replay.is_finished());
+        assert_eq!(solution.token, "TOKEN");
         Ok(())
     }
 }
@@ -17,7 +17,7 @@ use crate::captcha::captcha;
 ///     .website_url(Url::parse("https://2captcha.com/demo/lemin")?)
 ///     .captcha_id("CROPPED_3dfdd5c_d1872b526b794d83ba3b365eb15a200b")
 ///     .div_id("lemin-cropped-captcha")
-///     .lemin_api_server_subdomain(Url::parse("https://api.leminnow.com")?)
+///     .lemin_api_server_subdomain("api.leminnow.com")
 ///     .build();
 /// # Ok::<_, captcha_oxide::Error>(())
 /// ```
@@ -62,16 +62,13 @@ pub struct LeminCaptchaSolution<'a> {
 
 #[cfg(test)]
 mod test {
-    use std::env;
-
     use url::Url;
 
-    use crate::{captcha::types::lemin_captcha::LeminCaptcha, Captcha, CaptchaSolver, Error};
+    use crate::{captcha::types::lemin_captcha::LeminCaptcha, mock, Captcha, Error};
 
     #[tokio::test]
     async fn lemin_captcha() -> Result<(), Error> {
-        dotenv::dotenv().unwrap();
-        let solver = CaptchaSolver::new(env::var("API_KEY").unwrap());
+        let (solver, replay) = mock::fixture("lemin_captcha");
 
         let captcha = LeminCaptcha::builder()
             .website_url(Url::parse("https://2captcha.com/demo/lemin")?)
@@ -80,9 +77,14 @@ mod test {
             .lemin_api_server_subdomain("api.leminnow.com")
             .build();
 
-        let solution = solver.solve(&captcha).await?.solution;
+        let solution = mock::solved(solver.solve(&captcha).await)?.solution;
 
-        assert_ne!(solution.answer, "");
+        assert!(replay.is_finished());
+        assert_eq!(solution.answer, "0xaxakx0xaxaax0xkxx3ox0x3ox3ox_...");
+        assert_eq!(
+            solution.challenge_id,
+            "e0348984-92ec-23af-1488-446e3a58946c"
+        );
 
         Ok(())
     }
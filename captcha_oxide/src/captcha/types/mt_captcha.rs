@@ -45,16 +45,13 @@ pub struct MtCaptchaSolution<'a> {
 
 #[cfg(test)]
 mod test {
-    use std::env;
-
     use url::Url;
 
-    use crate::{captcha::types::mt_captcha::MtCaptcha, Captcha, CaptchaSolver, Error};
+    use crate::{captcha::types::mt_captcha::MtCaptcha, mock, Captcha, Error};
 
     #[tokio::test]
     async fn mt_captcha() -> Result<(), Error> {
-        dotenv::dotenv().unwrap();
-        let solver = CaptchaSolver::new(env::var("API_KEY").unwrap());
+        let (solver, replay) = mock::fixture("mt_captcha");
 
         let captcha = MtCaptcha::builder()
             .website_url(Url::parse(
@@ -63,9 +60,10 @@ mod test {
             .website_key("MTPublic-DemoKey9M")
             .build();
 
-        let solution = solver.solve(&captcha).await?.solution;
+        let solution = mock::solved(solver.solve(&captcha).await)?.solution;
 
-        assert_ne!(solution.token, "");
+        assert!(replay.is_finished());
+        assert_eq!(solution.token, "v1(03,79a,MTPublic-DemoKey9M,...)");
         Ok(())
     }
 }
@@ -76,26 +76,25 @@ pub struct RecaptchaV2<'a> {
 
 #[cfg(test)]
 mod test {
-    use dotenv::dotenv;
-    use std::env;
     use url::Url;
 
-    use crate::{captcha::types::recaptcha::v2::RecaptchaV2, Captcha, CaptchaSolver, Error};
+    use crate::{captcha::types::recaptcha::v2::RecaptchaV2, mock, Captcha, Error};
 
     #[tokio::test]
     async fn recaptcha_v2() -> Result<(), Error> {
-        dotenv().unwrap();
+        let (solver, replay) = mock::fixture("recaptcha_v2");
 
         let data = RecaptchaV2::builder()
             .website_url(Url::parse("https://patrickhlauke.github.io/recaptcha/")?)
             .website_key("6Ld2sf4SAAAAAKSgzs0Q13IZhY02Pyo31S2jgOB5")
             .build();
 
-        let solver = CaptchaSolver::new(env::var("API_KEY").unwrap());
+        let solution = mock::solved(solver.solve(&data).await)?
+            .solution
+            .g_recaptcha_response;
 
-        let solution = solver.solve(&data).await?.solution.g_recaptcha_response;
-
-        assert!(!solution.is_empty());
+        assert!(replay.is_finished());
+        assert_eq!(solution, "03ADUVZw...UWxTAe6ncIa");
         Ok(())
     }
 }
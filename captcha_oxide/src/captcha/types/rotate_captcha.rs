@@ -18,7 +18,12 @@ use crate::captcha::captcha;
 ///     .angle(60_u16)
 ///     .build();
 /// ```
-#[captcha(crate = "crate", timeout = 5, solution = "RotateCaptchaSolution")]
+#[captcha(
+    crate = "crate",
+    timeout = 5,
+    poll_interval = 3,
+    solution = "RotateCaptchaSolution"
+)]
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase", tag = "type", rename = "RotateTask")]
 pub struct RotateCaptcha<'a> {
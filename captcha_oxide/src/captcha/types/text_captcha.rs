@@ -18,7 +18,12 @@ use serde::{Deserialize, Serialize};
 ///     .comment("What's 2 + 2?")
 ///     .build();
 /// ```
-#[captcha(crate = "crate", timeout = 5, solution = "TextCaptchaSolution<'a>")]
+#[captcha(
+    crate = "crate",
+    timeout = 5,
+    poll_interval = 3,
+    solution = "TextCaptchaSolution<'a>"
+)]
 #[derive(Debug, Serialize)]
 #[serde(tag = "type", rename = "TextCaptchaTask")]
 pub struct TextCaptcha<'a> {
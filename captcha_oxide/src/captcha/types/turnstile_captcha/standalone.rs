@@ -47,20 +47,15 @@ pub struct StandaloneCaptcha<'a> {
 
 #[cfg(test)]
 mod test {
-    use std::env;
-
     use url::Url;
 
     use crate::{
-        captcha::types::turnstile_captcha::standalone::StandaloneCaptcha, Captcha, CaptchaSolver,
-        Error,
+        captcha::types::turnstile_captcha::standalone::StandaloneCaptcha, mock, Captcha, Error,
     };
 
     #[tokio::test]
     async fn turnstile_standalone_captcha() -> Result<(), Error> {
-        dotenv::dotenv().unwrap();
-
-        let solver = CaptchaSolver::new(env::var("API_KEY").unwrap());
+        let (solver, replay) = mock::fixture("turnstile_standalone");
 
         let captcha = StandaloneCaptcha::builder()
             .website_url(Url::parse(
@@ -69,8 +64,9 @@ mod test {
             .website_key("0x4AAAAAAAVrOwQWPlm3Bnr5")
             .build();
 
-        let solution = solver.solve(&captcha).await?.solution;
+        let solution = mock::solved(solver.solve(&captcha).await)?.solution;
 
+        assert!(replay.is_finished());
         assert!(!solution.token.is_empty(), "Empty?");
 
         Ok(())
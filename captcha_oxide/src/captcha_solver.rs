@@ -1,8 +1,9 @@
-use std::time::Duration;
+use std::{borrow::Cow, future::Future, sync::Arc};
 
+use futures_util::Stream;
 use lazy_static::lazy_static;
-use rquest::Client;
-use tokio::time::sleep;
+use serde::Serialize;
+use tokio::time::{sleep_until, Instant};
 use url::Url;
 
 use crate::{
@@ -11,35 +12,95 @@ use crate::{
         Captcha,
     },
     language_pool::LanguagePool,
-    two_captcha::{create_task, get_balance, get_task_result, report},
-    Result, SOFT_ID,
+    two_captcha::{create_task, get_task_result, pingback_addresses, report},
+    two_captcha_v1::{res, submit},
+    Money, Result,
 };
 
-use self::builder::{CaptchaSolverBuilder, MissingApiKey};
+pub use self::{
+    balance::BalanceWatch,
+    budget::{Budget, BudgetPeriod},
+    builder::{ApiKey, CaptchaSolverBuilder, MissingApiKey},
+    event::Event,
+    failover::{BackendHealth, FailoverSolution, FailoverSolver, FailoverSolverBuilder, Selection},
+    key_pool::KeyConfig,
+    metrics::{Retry, RetryStage, SolveMetrics},
+    options::SolveOptions,
+    pending_task::PendingTask,
+    protocol::Protocol,
+    provider::Provider,
+    retry::RetryPolicy,
+    stats::{SolveStats, TaskStats},
+    task_id::TaskId,
+};
+
+#[cfg(feature = "pingback")]
+pub use self::pingback::PingbackReceiver;
+
+/// Re-exported so solves can be cancelled without depending on `tokio-util`
+pub use tokio_util::sync::CancellationToken;
+
+use self::{
+    api::Api, balance::BalanceWatcher, budget::Job, event::EventHandler, key_pool::Lease,
+    metrics::Trace, options::DEFAULT_POLL_INTERVAL, poller::Poller, spans::record_error,
+    stats::StatsCollector,
+};
 
+#[macro_use]
+mod spans;
+
+mod api;
+mod balance;
+mod batch;
+mod budget;
 mod builder;
 pub mod error;
+mod event;
+mod failover;
+mod key_pool;
+mod metrics;
+mod options;
+mod pending_task;
+#[cfg(feature = "pingback")]
+mod pingback;
+mod poller;
+mod protocol;
+mod provider;
+mod rate_limit;
+mod retry;
+mod stats;
+mod task_id;
 
 lazy_static! {
-    static ref CLIENT: Client = Client::new();
-    static ref API_URL: Url = Url::parse("https://api.2captcha.com/").unwrap();
-    static ref CREATE_TASK_URL: Url = API_URL.join("/createTask").unwrap();
-    static ref GET_TASK_RESULT_URL: Url = API_URL.join("/getTaskResult").unwrap();
-    static ref GET_BALANCE_URL: Url = API_URL.join("/getBalance").unwrap();
-    static ref REPORT_CORRECT_URL: Url = API_URL.join("/reportCorrect").unwrap();
-    static ref REPORT_INCORRECT_URL: Url = API_URL.join("/reportIncorrect").unwrap();
+    pub(crate) static ref DEFAULT_API_URL: Url = Url::parse("https://api.2captcha.com/").unwrap();
+    pub(crate) static ref DEFAULT_LEGACY_API_URL: Url =
+        Url::parse("https://2captcha.com/").unwrap();
 }
 
 /// This struct is responsible for handling all of your interactions with the
-/// 2captcha API. Use the [`CaptchaSolver::new`] method to instantiate it with
+/// 2captcha API.
+///
+/// Use the [`CaptchaSolver::new`] method to instantiate it with
 /// a given API key or the [`CaptchaSolver::builder`] method to configure other
-/// settings
+/// settings.
+///
+/// Every task the solver is waiting on is polled from a single background
+/// task, which is started on the current tokio runtime the first time a
+/// task is waited on
 pub struct CaptchaSolver {
-    api_key: Box<str>,
+    api: Arc<Api>,
+    poller: Poller,
     language_pool: LanguagePool,
+    solve_options: SolveOptions,
+    on_event: Option<EventHandler>,
+    balance_watcher: Option<BalanceWatcher>,
+    stats: StatsCollector,
 
     #[cfg(feature = "callback")]
     callback_url: Option<Url>,
+
+    #[cfg(feature = "pingback")]
+    pingback: Option<PingbackReceiver>,
 }
 
 impl CaptchaSolver {
@@ -49,13 +110,7 @@ impl CaptchaSolver {
     where
         T: Into<Box<str>>,
     {
-        Self {
-            api_key: api_key.into(),
-            language_pool: LanguagePool::En,
-
-            #[cfg(feature = "callback")]
-            callback_url: None,
-        }
+        Self::builder().api_key(KeyConfig::new(api_key)).build()
     }
 
     /// Returns a new instance of `CaptchaSolverBuilder`, which allows you to configure
@@ -70,16 +125,33 @@ impl CaptchaSolver {
     ///
     /// # Errors
     /// This function can error if the HTTP request is not sent successfully,
-    /// if the response cannot be parsed or if the 2captcha API returns an error
+    /// if the response cannot be parsed, if the 2captcha API returns an error
+    /// or if the task is not solved before the solver's deadline
     pub async fn solve<'a, T>(&self, task: &T) -> Result<Solution<'a, T>>
     where
         T: Captcha,
     {
-        let task_id = self.create_task(task).await?;
-
-        sleep(task.get_timeout()).await;
+        self.solve_with(task, SolveOptions::new()).await
+    }
 
-        self.get_task_result(task_id).await
+    #[cfg(not(feature = "callback"))]
+    /// Sends a request to the 2captcha API to solve the given puzzle, overriding
+    /// the solver's [`SolveOptions`] with the ones that are set in `options`
+    ///
+    /// # Errors
+    /// This function can error if the HTTP request is not sent successfully,
+    /// if the response cannot be parsed, if the 2captcha API returns an error
+    /// or if the task is not solved before the deadline
+    pub async fn solve_with<'a, T>(
+        &self,
+        task: &T,
+        options: SolveOptions,
+    ) -> Result<Solution<'a, T>>
+    where
+        T: Captcha,
+    {
+        self.solve_until(task, options.or(self.solve_options), None, &Job::default())
+            .await
     }
 
     #[cfg(feature = "callback")]
@@ -87,77 +159,512 @@ impl CaptchaSolver {
     ///
     /// # Errors
     /// This function can error if the HTTP request is not sent successfully,
-    /// if the response cannot be parsed or if the 2captcha API returns an error
+    /// if the response cannot be parsed, if the 2captcha API returns an error
+    /// or if the task is not solved before the solver's deadline
     ///
     /// # Option
     /// This function will only ever return `Ok(None)` if the `CaptchaSolver::callback_url`
-    /// field is set. Otherwise, it is safe to `unwrap` the [`Option`] within the [`Result`]
+    /// field is set and there is no [`PingbackReceiver`] to wait for the pingback with.
+    /// Otherwise, it is safe to `unwrap` the [`Option`] within the [`Result`]
     pub async fn solve<'a, T>(&self, task: &T) -> Result<Option<Solution<'a, T>>>
     where
         T: Captcha,
     {
-        let task_id = self.create_task(task).await?;
+        self.solve_with(task, SolveOptions::new()).await
+    }
+
+    #[cfg(feature = "callback")]
+    /// Sends a request to the 2captcha API to solve the given puzzle, overriding
+    /// the solver's [`SolveOptions`] with the ones that are set in `options`
+    ///
+    /// # Errors
+    /// This function can error if the HTTP request is not sent successfully,
+    /// if the response cannot be parsed, if the 2captcha API returns an error
+    /// or if the task is not solved before the deadline
+    ///
+    /// # Option
+    /// This function will only ever return `Ok(None)` if the `CaptchaSolver::callback_url`
+    /// field is set and there is no [`PingbackReceiver`] to wait for the pingback with.
+    /// Otherwise, it is safe to `unwrap` the [`Option`] within the [`Result`]
+    pub async fn solve_with<'a, T>(
+        &self,
+        task: &T,
+        options: SolveOptions,
+    ) -> Result<Option<Solution<'a, T>>>
+    where
+        T: Captcha,
+    {
+        let options = options.or(self.solve_options);
 
-        if self.callback_url.is_some() {
+        if self.callback_url.is_some() && !self.receives_pingbacks() {
+            let deadline = Instant::now().checked_add(options.get_deadline());
+            self.create_task(task, deadline, None, &Job::default(), &Trace::new())
+                .await?;
             return Ok(None);
         }
 
-        sleep(task.get_timeout()).await;
+        self.solve_until(task, options, None, &Job::default())
+            .await
+            .map(Some)
+    }
 
-        self.get_task_result(task_id).await.map(Some)
+    /// Same as [`CaptchaSolver::solve_with`], but stops as soon as `cancel`
+    /// is cancelled instead of waiting for the solution. The solution is
+    /// always waited for, even if a `callback_url` is set
+    ///
+    /// # Errors
+    /// Besides the errors returned by [`CaptchaSolver::solve_with`], this
+    /// function returns [`crate::Error::Cancelled`] with the id of the task
+    /// that was abandoned, if it had been created, when `cancel` is
+    /// cancelled. An [`Event::TaskAbandoned`] is emitted for that task
+    pub async fn solve_cancellable<'a, T>(
+        &self,
+        task: &T,
+        options: SolveOptions,
+        cancel: &CancellationToken,
+    ) -> Result<Solution<'a, T>>
+    where
+        T: Captcha,
+    {
+        self.solve_until(
+            task,
+            options.or(self.solve_options),
+            Some(cancel),
+            &Job::default(),
+        )
+        .await
+    }
+
+    /// Solves every task in `tasks`, keeping at most `concurrency` of them
+    /// submitted at a time.
+    ///
+    /// Results are yielded in the order the tasks finish, along with the
+    /// index of the task in `tasks`. Like every other task, the tasks in
+    /// flight are polled by the solver's background poller, so there is a
+    /// single polling loop however large the batch is. Each poll is still a
+    /// request of its own, as `getTaskResult` only takes a single task id.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use captcha_oxide::{
+    ///     CaptchaSolver,
+    ///     captcha::types::normal_captcha::NormalCaptcha,
+    ///     Captcha,
+    /// };
+    /// use futures_util::StreamExt;
+    ///
+    /// async fn example(images: &[&str]) {
+    ///     let solver = CaptchaSolver::new("YOUR TWOCAPTCHA API KEY");
+    ///     let captchas: Vec<_> = images
+    ///         .iter()
+    ///         .map(|&image| NormalCaptcha::builder().body(image).build())
+    ///         .collect();
+    ///
+    ///     let mut solutions = Box::pin(solver.solve_many(&captchas, 10));
+    ///     while let Some((index, solution)) = solutions.next().await {
+    ///         match solution {
+    ///             Ok(solution) => println!("{index}: {}", solution.solution.text),
+    ///             Err(e) => println!("{index} failed: {e}"),
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn solve_many<'s, 'a, T, I>(
+        &'s self,
+        tasks: I,
+        concurrency: usize,
+    ) -> impl Stream<Item = (usize, Result<Solution<'a, T>>)> + 's
+    where
+        T: Captcha + 's,
+        I: IntoIterator<Item = &'s T>,
+        I::IntoIter: Send + 's,
+        'a: 's,
+    {
+        self.solve_many_with(tasks, concurrency, SolveOptions::new())
+    }
+
+    /// Same as [`CaptchaSolver::solve_many`], but overriding the solver's
+    /// [`SolveOptions`] with the ones that are set in `options` for every
+    /// task in the batch
+    pub fn solve_many_with<'s, 'a, T, I>(
+        &'s self,
+        tasks: I,
+        concurrency: usize,
+        options: SolveOptions,
+    ) -> impl Stream<Item = (usize, Result<Solution<'a, T>>)> + 's
+    where
+        T: Captcha + 's,
+        I: IntoIterator<Item = &'s T>,
+        I::IntoIter: Send + 's,
+        'a: 's,
+    {
+        batch::solve_many(self, tasks, concurrency, options)
     }
 
-    async fn create_task<T>(&self, task: &T) -> Result<u64>
+    /// Creates a task for the given puzzle without waiting for it to be solved.
+    /// The returned [`PendingTask`] can be used to wait for the solution or to
+    /// get the task's id and resume it later
+    ///
+    /// # Errors
+    /// This function can error if the HTTP request is not sent successfully,
+    /// if the response cannot be parsed or if the 2captcha API returns an error
+    pub async fn submit<T>(&self, task: &T) -> Result<PendingTask<'_, T>>
     where
         T: Captcha,
+    {
+        self.submit_with(task, SolveOptions::new()).await
+    }
+
+    /// Creates a task for the given puzzle without waiting for it to be solved,
+    /// overriding the solver's [`SolveOptions`] with the ones that are set in
+    /// `options`
+    ///
+    /// # Errors
+    /// This function can error if the HTTP request is not sent successfully,
+    /// if the response cannot be parsed or if the 2captcha API returns an error
+    pub async fn submit_with<T>(
+        &self,
+        task: &T,
+        options: SolveOptions,
+    ) -> Result<PendingTask<'_, T>>
+    where
+        T: Captcha,
+    {
+        let options = options.or(self.solve_options);
+        let deadline = Instant::now().checked_add(options.get_deadline());
+
+        self.submit_before(task, &options, deadline, None, &Job::default())
+            .await
+    }
+
+    /// Returns a [`PendingTask`] for a task that was created earlier, e.g. by
+    /// a process that has since been restarted, so you can keep polling it
+    /// with the key it was created with. The deadline is counted from the
+    /// moment this function is called
+    pub fn resume<T>(&self, task_id: TaskId) -> PendingTask<'_, T>
+    where
+        T: Captcha,
+    {
+        self.resume_with(task_id, SolveOptions::new())
+    }
+
+    /// Same as [`CaptchaSolver::resume`], but overriding the solver's
+    /// [`SolveOptions`] with the ones that are set in `options`. As the task
+    /// was already created, the initial delay is ignored
+    pub fn resume_with<T>(&self, task_id: TaskId, options: SolveOptions) -> PendingTask<'_, T>
+    where
+        T: Captcha,
+    {
+        let options = options.or(self.solve_options);
+        let now = Instant::now();
+
+        PendingTask::new(
+            self,
+            task_id,
+            options.get_poll_interval(DEFAULT_POLL_INTERVAL),
+            now,
+            now.checked_add(options.get_deadline()),
+            Trace::new(),
+            None,
+        )
+    }
+
+    /// Submits `task` and waits for its solution, submitting it again if
+    /// the [`RetryPolicy`] allows it when it cannot be solved. The cost of
+    /// the solution is added to the spend of `job`, and the outcome to the
+    /// solver's stats
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "solve",
+            skip_all,
+            fields(task_type, task_id, polls, error_code)
+        )
+    )]
+    async fn solve_until<'a, T>(
+        &self,
+        task: &T,
+        options: SolveOptions,
+        cancel: Option<&CancellationToken>,
+        job: &Job,
+    ) -> Result<Solution<'a, T>>
+    where
+        T: Captcha,
+    {
+        let task_type = task.get_task_type();
+        record!(task_type = task_type);
+
+        let result = record_error(self.solve_attempts(task, options, cancel, job).await);
+
+        match &result {
+            Ok(solution) => {
+                record!(polls = solution.metrics.polls);
+                self.stats
+                    .solved(task_type, solution.metrics.wall_time, solution.cost);
+            }
+            Err(e) => self.stats.failed(task_type, e),
+        }
+
+        result
+    }
+
+    async fn solve_attempts<'a, T>(
+        &self,
+        task: &T,
+        options: SolveOptions,
+        cancel: Option<&CancellationToken>,
+        job: &Job,
+    ) -> Result<Solution<'a, T>>
+    where
+        T: Captcha,
+    {
+        let started = Instant::now();
+        let deadline = started.checked_add(options.get_deadline());
+        let mut attempt = 1;
+        let mut earlier = SolveMetrics::default();
+
+        loop {
+            if cancel.is_some_and(CancellationToken::is_cancelled) {
+                return Err(crate::Error::Cancelled { task_id: None });
+            }
+
+            let pending = self
+                .submit_before(task, &options, deadline, cancel, job)
+                .await?;
+            record!(task_id = u64::from(pending.task_id()));
+
+            let result = match cancel {
+                Some(cancel) => pending.wait_cancellable(cancel).await,
+                None => pending.wait().await,
+            };
+
+            match result {
+                Err(e) if self.api.retry_policy.should_resubmit(&e, attempt) => {
+                    earlier = pending.metrics().after(earlier, started);
+                    earlier.resubmitted(&e);
+                    attempt += 1;
+                }
+                Ok(mut solution) => {
+                    solution.metrics = solution.metrics.after(earlier, started);
+
+                    if let (Some(budget), Some(cost)) = (&self.api.budget, solution.cost) {
+                        budget.record_job(job, cost);
+                    }
+
+                    return Ok(solution);
+                }
+                result => {
+                    record!(polls = pending.metrics().polls + earlier.polls);
+                    return result;
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "pingback")]
+    const fn receives_pingbacks(&self) -> bool {
+        self.pingback.is_some()
+    }
+
+    #[cfg(all(feature = "callback", not(feature = "pingback")))]
+    #[allow(clippy::unused_self)]
+    const fn receives_pingbacks(&self) -> bool {
+        false
+    }
+
+    fn emit(&self, event: &Event) {
+        if let Some(on_event) = &self.on_event {
+            on_event(event);
+        }
+    }
+
+    async fn submit_before<T>(
+        &self,
+        task: &T,
+        options: &SolveOptions,
+        deadline: Option<Instant>,
+        cancel: Option<&CancellationToken>,
+        job: &Job,
+    ) -> Result<PendingTask<'_, T>>
+    where
+        T: Captcha,
+    {
+        let trace = Trace::new();
+        let (task_id, lease) = self
+            .create_task(task, deadline, cancel, job, &trace)
+            .await?;
+        let ready_at = Instant::now() + options.get_initial_delay(task.get_timeout());
+
+        Ok(PendingTask::new(
+            self,
+            task_id,
+            options.get_poll_interval(task.get_poll_interval()),
+            ready_at,
+            deadline,
+            trace,
+            Some(lease),
+        ))
+    }
+
+    /// Creates the task with the next key of the pool, returning the lease
+    /// on the key along with the task's id, unless the budget for `job` is
+    /// exhausted. Waits while submissions are paused for a low balance or
+    /// every key is at its concurrency cap, until `cancel` is cancelled or
+    /// `deadline` passes. The latency and retries of the request are
+    /// recorded in `trace`
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(task_type = task.get_task_type(), task_id, error_code)
+        )
+    )]
+    async fn create_task<T>(
+        &self,
+        task: &T,
+        deadline: Option<Instant>,
+        cancel: Option<&CancellationToken>,
+        job: &Job,
+        trace: &Trace,
+    ) -> Result<(TaskId, Lease<'_>)>
+    where
+        T: Captcha,
+    {
+        let created = record_error(
+            self.send_create_task(task, deadline, cancel, job, trace)
+                .await,
+        )?;
+        record!(task_id = u64::from(created.0));
+
+        Ok(created)
+    }
+
+    async fn send_create_task<T>(
+        &self,
+        task: &T,
+        deadline: Option<Instant>,
+        cancel: Option<&CancellationToken>,
+        job: &Job,
+        trace: &Trace,
+    ) -> Result<(TaskId, Lease<'_>)>
+    where
+        T: Captcha,
+    {
+        if let Some(budget) = &self.api.budget {
+            budget.check(job)?;
+        }
+
+        if let Some(balance_watcher) = &self.balance_watcher {
+            interruptible(balance_watcher.wait_until_funded(), deadline, cancel).await?;
+        }
+
+        let lease = interruptible(self.api.keys.acquire(), deadline, cancel).await??;
+
+        // Other tasks may have been solved while this one was waiting
+        if let Some(budget) = &self.api.budget {
+            budget.check(job)?;
+        }
+        let sent = Instant::now();
+
+        let task_id = if self.api.protocol == Protocol::V1 {
+            self.submit_form(task, lease.key(), trace).await?
+        } else {
+            match self.api.provider.rename_task(task)? {
+                Some(task) => self.send_task(&task, lease.key(), trace).await?,
+                None => self.send_task(task, lease.key(), trace).await?,
+            }
+        };
+
+        trace.submitted(sent.elapsed());
+        job.created();
+
+        Ok((lease.pin(task_id), lease))
+    }
+
+    /// Sends `task`, which may have been renamed for the provider, to
+    /// `createTask`
+    async fn send_task<T>(&self, task: &T, key: &str, trace: &Trace) -> Result<TaskId>
+    where
+        T: Serialize + Sync,
     {
         let request = create_task::Request {
-            client_key: &self.api_key,
+            client_key: key,
             task,
-            soft_id: SOFT_ID,
+            soft_id: self.api.provider.soft_id_field(),
             #[cfg(feature = "callback")]
             callback_url: self.callback_url.as_ref(),
             language_pool: self.language_pool,
         };
 
-        let response = CLIENT
-            .post(CREATE_TASK_URL.as_ref())
-            .json(&request)
-            .send()
-            .await?
-            .json::<create_task::Response>()
-            .await?;
+        self.api
+            .retry_policy
+            .run_submit(
+                || async {
+                    let response: create_task::Response =
+                        self.api.post("createTask", &request).await?;
 
-        Ok(error::Result::from(response)?)
+                    Ok(error::Result::from(response)?.into())
+                },
+                |e| trace.retried(RetryStage::Submit, e),
+            )
+            .await
     }
 
-    async fn get_task_result<'a, T>(&self, task_id: u64) -> Result<Solution<'a, T>>
+    /// Creates the task through `in.php`, for [`Protocol::V1`]
+    async fn submit_form<T>(&self, task: &T, key: &str, trace: &Trace) -> Result<TaskId>
     where
         T: Captcha,
     {
-        let request = get_task_result::Request {
-            client_key: &self.api_key,
-            task_id,
+        let params = submit::params(&serde_json::to_value(task)?)
+            .ok_or_else(|| error::ApiError::from(error::Error::TaskNotSupported))?;
+
+        let request = submit::Request {
+            key,
+            soft_id: self.api.provider.soft_id_value(),
+            json: 1,
+            #[cfg(feature = "callback")]
+            pingback: self.callback_url.as_ref(),
+            params,
         };
 
-        loop {
-            let response = CLIENT
-                .post(GET_TASK_RESULT_URL.as_ref())
-                .json(&request)
-                .send()
-                .await?
-                .json::<get_task_result::Response<'a, T>>()
-                .await?;
+        self.api
+            .retry_policy
+            .run_submit(
+                || async {
+                    let response: submit::Response = self.api.post_form("in.php", &request).await?;
 
-            let captcha_solution = error::Result::<_>::from(response)?;
+                    Ok(error::Result::from(response)?.into())
+                },
+                |e| trace.retried(RetryStage::Submit, e),
+            )
+            .await
+    }
 
-            if let Some(mut captcha_solution) = captcha_solution {
-                captcha_solution.task_id = task_id;
-                return Ok(captcha_solution);
-            }
+    /// Asks for the result of the given task once, returning `Ok(None)` if
+    /// it is still being processed. The polls are recorded in `trace`
+    pub(crate) async fn get_task_result<'a, T>(
+        &self,
+        task_id: TaskId,
+        trace: &Trace,
+    ) -> Result<Option<Solution<'a, T>>>
+    where
+        T: Captcha,
+    {
+        let response = self
+            .api
+            .retry_policy
+            .run_traced(
+                || async {
+                    let response = self.api.task_result(task_id).await;
+                    trace.polled();
 
-            sleep(Duration::from_secs(5)).await;
-        }
+                    parse_task_result(task_id, &response?)
+                },
+                |e| trace.retried(RetryStage::Poll, e),
+            )
+            .await?;
+
+        Ok(response)
     }
 
     /// Sends a request to the 2captcha API to return your current balance
@@ -165,20 +672,30 @@ impl CaptchaSolver {
     /// # Errors
     /// This function can error if the HTTP request is not sent successfully,
     /// if the response cannot be parsed or if the 2captcha API returns an error
-    pub async fn get_balance(&self) -> Result<f64> {
-        let request = get_balance::Request {
-            client_key: &self.api_key,
-        };
+    pub async fn get_balance(&self) -> Result<Money> {
+        self.api.balance().await
+    }
 
-        let response = CLIENT
-            .post(GET_BALANCE_URL.as_ref())
-            .json(&request)
-            .send()
-            .await?
-            .json::<get_balance::Response>()
-            .await?;
+    /// Returns a receiver for the latest balance read by the background task
+    /// configured with [`CaptchaSolverBuilder::balance_watch`], starting the
+    /// task if it is not running. The value is `None` until the balance is
+    /// first read. Returns `None` if the balance is not being watched
+    #[must_use]
+    pub fn watch_balance(&self) -> Option<tokio::sync::watch::Receiver<Option<Money>>> {
+        self.balance_watcher.as_ref().map(BalanceWatcher::subscribe)
+    }
+
+    /// Statistics about every task solved or failed through the `solve`
+    /// methods of this solver, by task type. Tasks that were only submitted
+    /// or resumed are not counted
+    #[must_use]
+    pub fn stats(&self) -> SolveStats {
+        self.stats.snapshot()
+    }
 
-        error::Result::<_>::from(response).map_err(Into::into)
+    /// Forgets the statistics collected so far
+    pub fn reset_stats(&self) {
+        self.stats.reset();
     }
 
     /// Sends a request to the 2captcha API infroming whether or not the solution
@@ -187,26 +704,520 @@ impl CaptchaSolver {
     /// # Errors
     /// This function can error if the HTTP request is not sent successfully,
     /// if the response cannot be parsed or if the 2captcha API returns an error
-    pub async fn report<'a, T>(&self, solution: Solution<'a, T>, status: Status) -> Result<()>
+    pub async fn report<T>(&self, solution: Solution<'_, T>, status: Status) -> Result<()>
     where
         T: Captcha,
     {
+        self.report_by_id(solution.task_id, status).await
+    }
+
+    /// Sends a request to the 2captcha API infroming whether or not the solution
+    /// to the given task was valid. Use this when the [`Solution`] itself is
+    /// no longer available, e.g. after a restart
+    ///
+    /// # Errors
+    /// This function can error if the HTTP request is not sent successfully,
+    /// if the response cannot be parsed or if the 2captcha API returns an error
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "report",
+            skip_all,
+            fields(task_id = u64::from(task_id), ?status, error_code)
+        )
+    )]
+    pub async fn report_by_id(&self, task_id: TaskId, status: Status) -> Result<()> {
+        record_error(self.send_report(task_id, status).await)
+    }
+
+    async fn send_report(&self, task_id: TaskId, status: Status) -> Result<()> {
+        if self.api.protocol == Protocol::V1 {
+            let action = match status {
+                Status::Correct => res::Action::ReportGood,
+                Status::Incorrect => res::Action::ReportBad,
+            };
+
+            return self
+                .api
+                .legacy_action(action, Some(task_id))
+                .await
+                .map(drop);
+        }
+
         let request = report::Request {
-            client_key: &self.api_key,
-            task_id: solution.task_id,
+            client_key: self.api.keys.key_for(task_id)?,
+            task_id: task_id.into(),
+        };
+
+        let method = match status {
+            Status::Correct => "reportCorrect",
+            Status::Incorrect => "reportIncorrect",
         };
 
-        let response = CLIENT
-            .post(match status {
-                Status::Correct => REPORT_CORRECT_URL.as_ref(),
-                Status::Incorrect => REPORT_INCORRECT_URL.as_ref(),
+        self.api
+            .retry_policy
+            .run(|| async {
+                let response: report::Response = self.api.post(method, &request).await?;
+
+                error::Result::<_>::from(response).map_err(Into::into)
             })
-            .json(&request)
-            .send()
-            .await?
-            .json::<report::Response>()
+            .await
+    }
+
+    /// Registers `url` as an address 2captcha is allowed to send pingbacks
+    /// to, which is required before it can be used as a callback URL
+    ///
+    /// # Errors
+    /// This function can error if the HTTP request is not sent successfully,
+    /// if the response cannot be parsed or if the 2captcha API returns an error
+    pub async fn add_pingback(&self, url: &Url) -> Result<()> {
+        self.manage_pingbacks(pingback_addresses::Action::Add, Some(url.as_str()))
+            .await
+            .map(drop)
+    }
+
+    /// Returns every address registered for pingbacks
+    ///
+    /// # Errors
+    /// This function can error if the HTTP request is not sent successfully,
+    /// if the response cannot be parsed or if the 2captcha API returns an error
+    pub async fn get_pingbacks(&self) -> Result<Vec<String>> {
+        let payload = self
+            .manage_pingbacks(pingback_addresses::Action::Get, None)
             .await?;
 
-        error::Result::<_>::from(response).map_err(Into::into)
+        Ok(match payload {
+            pingback_addresses::Payload::Addresses(addresses) => {
+                addresses.into_iter().map(Cow::into_owned).collect()
+            }
+            pingback_addresses::Payload::Text(addresses) => addresses
+                .split(',')
+                .filter(|address| !address.is_empty())
+                .map(str::to_owned)
+                .collect(),
+        })
+    }
+
+    /// Removes `url` from the addresses registered for pingbacks
+    ///
+    /// # Errors
+    /// This function can error if the HTTP request is not sent successfully,
+    /// if the response cannot be parsed or if the 2captcha API returns an error
+    pub async fn delete_pingback(&self, url: &Url) -> Result<()> {
+        self.manage_pingbacks(pingback_addresses::Action::Delete, Some(url.as_str()))
+            .await
+            .map(drop)
+    }
+
+    /// Removes every address registered for pingbacks
+    ///
+    /// # Errors
+    /// This function can error if the HTTP request is not sent successfully,
+    /// if the response cannot be parsed or if the 2captcha API returns an error
+    pub async fn delete_all_pingbacks(&self) -> Result<()> {
+        self.manage_pingbacks(pingback_addresses::Action::Delete, Some("all"))
+            .await
+            .map(drop)
+    }
+
+    async fn manage_pingbacks(
+        &self,
+        action: pingback_addresses::Action,
+        addr: Option<&str>,
+    ) -> Result<pingback_addresses::Payload<'static>> {
+        let request = pingback_addresses::Request {
+            key: self.api.keys.primary(),
+            action,
+            addr,
+            json: 1,
+        };
+
+        self.api
+            .retry_policy
+            .run(|| async {
+                let response: pingback_addresses::Response =
+                    self.api.post_form("res.php", &request).await?;
+
+                error::Result::<_>::from(response).map_err(Into::into)
+            })
+            .await
+    }
+}
+
+/// Waits for `future`, unless `cancel` is cancelled or `deadline` passes
+/// first, in which case the task was not created yet
+async fn interruptible<F>(
+    future: F,
+    deadline: Option<Instant>,
+    cancel: Option<&CancellationToken>,
+) -> Result<F::Output>
+where
+    F: Future,
+{
+    let cancelled = async {
+        match cancel {
+            Some(cancel) => cancel.cancelled().await,
+            None => std::future::pending().await,
+        }
+    };
+    let timed_out = async {
+        match deadline {
+            Some(deadline) => sleep_until(deadline).await,
+            None => std::future::pending().await,
+        }
+    };
+
+    tokio::select! {
+        output = future => Ok(output),
+        () = cancelled => Err(crate::Error::Cancelled { task_id: None }),
+        () = timed_out => Err(crate::Error::Timeout { task_id: None }),
+    }
+}
+
+/// Parses the body of a `getTaskResult` response, returning `Ok(None)` if the
+/// task is still being processed
+pub(crate) fn parse_task_result<'a, T>(
+    task_id: TaskId,
+    body: &[u8],
+) -> Result<Option<Solution<'a, T>>>
+where
+    T: Captcha,
+{
+    let response: get_task_result::Response<'a, T> = serde_json::from_slice(body)?;
+
+    Ok(error::Result::<_>::from(response)?.map(|mut solution| {
+        solution.task_id = task_id;
+        solution
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use captcha_oxide_testkit::{Fault, MockServer, TaskScript};
+    use serde_json::json;
+    use tokio_util::sync::CancellationToken;
+    use url::Url;
+
+    use super::{error::ErrorClass, Event, KeyConfig, Protocol, RetryPolicy, SolveOptions};
+    use crate::{
+        captcha::{solution::Status, types::grid_captcha::GridCaptcha},
+        mock, Captcha, Error, Money,
+    };
+
+    fn solved() -> TaskScript {
+        TaskScript::new()
+            .processing(2)
+            .ready(json!({ "text": "w68hp" }))
+    }
+
+    #[tokio::test]
+    async fn polls_through_server_errors() {
+        let server = MockServer::start().await;
+        server.enqueue(solved());
+        server.inject("getTaskResult", Fault::Status(502), 2);
+
+        let solution = mock::solved(mock::solver(&server).solve(&mock::captcha()).await).unwrap();
+
+        assert_eq!(solution.solution.text, "w68hp");
+    }
+
+    #[tokio::test]
+    async fn polls_through_dropped_connections() {
+        let server = MockServer::start().await;
+        server.enqueue(solved());
+        server.inject("getTaskResult", Fault::Drop, 2);
+
+        let solution = mock::solved(mock::solver(&server).solve(&mock::captcha()).await).unwrap();
+
+        assert_eq!(solution.solution.text, "w68hp");
+    }
+
+    #[tokio::test]
+    async fn malformed_json() {
+        let server = MockServer::start().await;
+        server.enqueue(solved());
+        server.enqueue(solved());
+        server.inject("getTaskResult", Fault::MalformedJson, 2);
+
+        let solution = mock::solved(mock::solver(&server).solve(&mock::captcha()).await).unwrap();
+
+        assert_eq!(solution.solution.text, "w68hp");
+
+        let solver = mock::builder(&server)
+            .retry_policy(RetryPolicy::none())
+            .build();
+        server.inject("getTaskResult", Fault::MalformedJson, 1);
+
+        let error = solver.solve(&mock::captcha()).await.unwrap_err();
+
+        assert!(matches!(error, Error::Serialize(_)));
+    }
+
+    #[tokio::test]
+    async fn slow_responses_count_towards_the_deadline() {
+        let server = MockServer::start().await;
+        server.enqueue(solved());
+        server.inject("getTaskResult", Fault::Delay(Duration::from_secs(5)), 1);
+
+        let options = SolveOptions::new().deadline(Duration::from_millis(200));
+        let error = mock::solver(&server)
+            .solve_with(&mock::captcha(), options)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, Error::Timeout { task_id } if task_id.map(u64::from) == Some(1)));
+    }
+
+    #[tokio::test]
+    async fn no_slot_storm() {
+        let server = MockServer::start().await;
+        server.enqueue(solved());
+        server.inject("createTask", Fault::ErrorCode("ERROR_NO_SLOT_AVAILABLE"), 5);
+
+        let solver = mock::solver(&server);
+        let error = solver.solve(&mock::captcha()).await.unwrap_err();
+
+        assert_eq!(error.class(), ErrorClass::Throttled);
+
+        let solver = mock::builder(&server)
+            .retry_policy(
+                RetryPolicy::default()
+                    .max_attempts(10)
+                    .initial_backoff(Duration::from_millis(1))
+                    .max_backoff(Duration::from_millis(5)),
+            )
+            .build();
+
+        let solution = mock::solved(solver.solve(&mock::captcha()).await).unwrap();
+
+        assert_eq!(solution.solution.text, "w68hp");
+    }
+
+    #[tokio::test]
+    async fn ambiguous_submit_failures_are_not_retried() {
+        for fault in [Fault::MalformedJson, Fault::Drop] {
+            let server = MockServer::start().await;
+            server.enqueue(solved());
+            server.inject("createTask", fault, 1);
+
+            let error = mock::solver(&server)
+                .solve(&mock::captcha())
+                .await
+                .unwrap_err();
+            let submissions = server
+                .requests()
+                .iter()
+                .filter(|request| request.path == "/createTask")
+                .count();
+
+            assert_eq!(error.class(), ErrorClass::Transient);
+            assert_eq!(submissions, 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn balance_runs_out_mid_batch() {
+        let server = MockServer::start().await;
+        server.set_balance(0.005);
+        for _ in 0..3 {
+            server.enqueue(solved().cost("0.003"));
+        }
+
+        let solver = mock::solver(&server);
+
+        assert!(solver.solve(&mock::captcha()).await.is_ok());
+
+        let error = solver.solve(&mock::captcha()).await.unwrap_err();
+
+        assert!(matches!(
+            error,
+            Error::TwoCaptchaError(ref e) if e.code() == "ERROR_ZERO_BALANCE"
+        ));
+        assert_eq!(error.class(), ErrorClass::Account);
+        assert!(solver.get_balance().await.unwrap() < Money::new(3, 3));
+    }
+
+    #[tokio::test]
+    async fn cancelling_abandons_the_task() {
+        let server = MockServer::start().await;
+        server.enqueue(TaskScript::new());
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let solver = mock::builder(&server)
+            .on_event({
+                let events = events.clone();
+                move |event| events.lock().unwrap().push(event.clone())
+            })
+            .build();
+
+        let cancel = CancellationToken::new();
+        tokio::spawn({
+            let cancel = cancel.clone();
+            async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                cancel.cancel();
+            }
+        });
+
+        let error = solver
+            .solve_cancellable(&mock::captcha(), SolveOptions::new(), &cancel)
+            .await
+            .unwrap_err();
+
+        let Error::Cancelled {
+            task_id: Some(task_id),
+        } = error
+        else {
+            panic!("{error:?} is not a cancellation");
+        };
+        assert_eq!(u64::from(task_id), 1);
+        assert_eq!(*events.lock().unwrap(), [Event::TaskAbandoned { task_id }]);
+    }
+
+    #[tokio::test]
+    async fn cancelled_before_submitting() {
+        let server = MockServer::start().await;
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+
+        let error = mock::solver(&server)
+            .solve_cancellable(&mock::captcha(), SolveOptions::new(), &cancel)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, Error::Cancelled { task_id: None }));
+        assert!(server.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn waiting_for_a_key_is_interruptible() {
+        let server = MockServer::start().await;
+        server.enqueue(TaskScript::new());
+
+        let solver = mock::builder(&server)
+            .api_key(KeyConfig::new("API_KEY").max_concurrent(1))
+            .build();
+        let _pending = solver.submit(&mock::captcha()).await.unwrap();
+
+        let cancel = CancellationToken::new();
+        tokio::spawn({
+            let cancel = cancel.clone();
+            async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                cancel.cancel();
+            }
+        });
+
+        let error = solver
+            .solve_cancellable(&mock::captcha(), SolveOptions::new(), &cancel)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::Cancelled { task_id: None }));
+
+        let options = SolveOptions::new().deadline(Duration::from_millis(50));
+        let error = solver
+            .solve_with(&mock::captcha(), options)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::Timeout { task_id: None }));
+    }
+
+    #[tokio::test]
+    async fn manages_pingbacks() {
+        let server = MockServer::start().await;
+        server.expect_api_key("API_KEY");
+
+        let solver = mock::solver(&server);
+        let first = Url::parse("https://example.com/pingback").unwrap();
+        let second = Url::parse("https://example.org/pingback").unwrap();
+
+        solver.add_pingback(&first).await.unwrap();
+        solver.add_pingback(&second).await.unwrap();
+
+        assert_eq!(
+            solver.get_pingbacks().await.unwrap(),
+            [first.as_str(), second.as_str()]
+        );
+
+        solver.delete_pingback(&first).await.unwrap();
+
+        assert_eq!(server.pingbacks(), [second.as_str()]);
+
+        solver.delete_all_pingbacks().await.unwrap();
+
+        assert!(solver.get_pingbacks().await.unwrap().is_empty());
+        assert_eq!(server.requests()[0].body["action"], json!("add_pingback"));
+    }
+
+    #[tokio::test]
+    async fn pingback_errors() {
+        let server = MockServer::start().await;
+        server.expect_api_key("ANOTHER_KEY");
+
+        let error = mock::solver(&server).get_pingbacks().await.unwrap_err();
+
+        assert_eq!(error.class(), ErrorClass::Account);
+    }
+
+    #[tokio::test]
+    async fn solves_through_v1() {
+        let server = MockServer::start().await;
+        server.expect_api_key("API_KEY");
+        server.enqueue(solved().cost("0.001"));
+
+        let solver = mock::builder(&server).protocol(Protocol::V1).build();
+        let solution = mock::solved(solver.solve(&mock::captcha()).await).unwrap();
+
+        assert_eq!(solution.solution.text, "w68hp");
+        assert_eq!(solution.cost, Some(Money::new(1, 3)));
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/in.php");
+        assert_eq!(requests[0].body["method"], json!("base64"));
+        assert_eq!(requests[0].body["body"], json!(mock::IMAGE));
+        assert!(requests[1..]
+            .iter()
+            .all(|request| request.path == "/res.php"));
+
+        solver
+            .report_by_id(solution.task_id(), Status::Incorrect)
+            .await
+            .unwrap();
+
+        assert_eq!(server.report(1), Some(false));
+        assert_eq!(solver.get_balance().await.unwrap(), Money::new(9_999, 3));
+    }
+
+    #[tokio::test]
+    async fn v1_errors() {
+        let server = MockServer::start().await;
+        server.enqueue(
+            TaskScript::new()
+                .processing(1)
+                .error("ERROR_CAPTCHA_UNSOLVABLE"),
+        );
+
+        let solver = mock::builder(&server)
+            .protocol(Protocol::V1)
+            .retry_policy(RetryPolicy::none())
+            .build();
+
+        let error = solver.solve(&mock::captcha()).await.unwrap_err();
+
+        assert_eq!(error.class(), ErrorClass::Unsolvable);
+
+        let grid = GridCaptcha::builder().body(mock::IMAGE).build();
+        let error = solver.solve(&grid).await.unwrap_err();
+
+        assert!(matches!(
+            error,
+            Error::TwoCaptchaError(ref e) if e.code() == "ERROR_TASK_NOT_SUPPORTED"
+        ));
+        assert_eq!(server.requests().len(), 3);
     }
 }
@@ -0,0 +1,250 @@
+use std::{
+    borrow::Cow,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use url::{form_urlencoded, Url};
+
+use crate::{
+    transport::Transport,
+    two_captcha::{get_balance, get_task_result},
+    two_captcha_v1::res::{self, Action},
+    Money, Result,
+};
+
+use super::{
+    budget::BudgetTracker, error, key_pool::KeyPool, rate_limit::RateLimit, spans::record_error,
+    Protocol, Provider, RetryPolicy, TaskId,
+};
+
+/// Everything needed to send requests to the API, shared between a
+/// [`crate::CaptchaSolver`] and its background poller
+pub struct Api {
+    pub keys: KeyPool,
+    pub url: Url,
+    pub legacy_url: Url,
+    pub protocol: Protocol,
+    pub provider: Provider,
+    pub transport: Arc<dyn Transport>,
+    pub retry_policy: RetryPolicy,
+    pub rate_limit: Option<RateLimit>,
+    pub budget: Option<BudgetTracker>,
+}
+
+/// The fields of a `getTaskResult` response needed to account for its cost
+/// and trace it
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Outcome<'a> {
+    #[serde(borrow)]
+    status: Option<Cow<'a, str>>,
+
+    cost: Option<Money>,
+
+    #[cfg(feature = "tracing")]
+    #[serde(borrow)]
+    error_code: Option<Cow<'a, str>>,
+}
+
+impl Api {
+    /// Sends `request` to the given API method through the configured
+    /// [`Transport`] once the rate limit allows it and returns the body of
+    /// the response
+    pub async fn send<R>(&self, method: &str, request: &R) -> Result<Vec<u8>>
+    where
+        R: Serialize + Sync,
+    {
+        let url = self.url.join(method)?;
+        let body = serde_json::to_vec(request)?;
+
+        if let Some(rate_limit) = &self.rate_limit {
+            rate_limit.acquire().await;
+        }
+
+        let response = self.transport.post_json(&url, body).await?;
+
+        Ok(self.provider.translate_error(response))
+    }
+
+    /// Sends `request` as a form to the given method of the legacy API, e.g.
+    /// `res.php`, and returns the body of the response. Every field of
+    /// `request` becomes a form field
+    pub async fn send_form<R>(&self, method: &str, request: &R) -> Result<Vec<u8>>
+    where
+        R: Serialize + Sync,
+    {
+        let url = self.legacy_url.join(method)?;
+        let body = form(request)?;
+
+        if let Some(rate_limit) = &self.rate_limit {
+            rate_limit.acquire().await;
+        }
+
+        let response = self.transport.post_form(&url, body).await?;
+
+        Ok(self.provider.translate_error(response))
+    }
+
+    /// Same as [`Api::send_form`], parsing the response
+    pub async fn post_form<R, S>(&self, method: &str, request: &R) -> Result<S>
+    where
+        R: Serialize + Sync,
+        S: DeserializeOwned,
+    {
+        let response = self.send_form(method, request).await?;
+
+        Ok(serde_json::from_slice(&response)?)
+    }
+
+    /// Asks for the result of the given task once, with the key it was
+    /// created with, and returns the body of a `getTaskResult` response.
+    /// Once the task is solved, its cost is added to the spend of the key
+    /// and to the budget
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "get_task_result",
+            skip_all,
+            fields(task_id = u64::from(task_id), error_code)
+        )
+    )]
+    pub async fn task_result(&self, task_id: TaskId) -> Result<Vec<u8>> {
+        let body = record_error(self.fetch_task_result(task_id).await)?;
+        let outcome = serde_json::from_slice::<Outcome>(&body).ok();
+        record!(error_code = outcome.as_ref().and_then(|o| o.error_code.as_deref()));
+
+        if let Some(cost) = outcome.and_then(Outcome::solved_cost) {
+            if self.keys.charge(task_id, cost) {
+                if let Some(budget) = &self.budget {
+                    budget.record(cost);
+                }
+            }
+        }
+
+        Ok(body)
+    }
+
+    /// With [`Protocol::V1`], the answer of `res.php` is translated into a
+    /// `getTaskResult` response
+    async fn fetch_task_result(&self, task_id: TaskId) -> Result<Vec<u8>> {
+        let key = self.keys.key_for(task_id)?;
+
+        if self.protocol == Protocol::V1 {
+            let request = res::Request {
+                key,
+                action: Action::Get2,
+                id: Some(task_id.into()),
+                json: 1,
+            };
+
+            let response: res::Response = self.post_form("res.php", &request).await?;
+            let body = error::Result::from(response)?.map_or_else(
+                || json!({ "errorId": 0, "status": "processing" }),
+                |response| {
+                    let now = SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map_or(0, |now| now.as_secs());
+
+                    response.into_task_result(now)
+                },
+            );
+
+            return Ok(serde_json::to_vec(&body)?);
+        }
+
+        let request = get_task_result::Request {
+            client_key: key,
+            task_id: task_id.into(),
+        };
+
+        self.send("getTaskResult", &request).await
+    }
+
+    /// Asks for the balance of the account of the primary key
+    pub async fn balance(&self) -> Result<Money> {
+        if self.protocol == Protocol::V1 {
+            let response = self.legacy_action(Action::GetBalance, None).await?;
+
+            return Ok(Money::deserialize(response.request)?);
+        }
+
+        let request = get_balance::Request {
+            client_key: self.keys.primary(),
+        };
+
+        self.retry_policy
+            .run(|| async {
+                let response: get_balance::Response = self.post("getBalance", &request).await?;
+
+                error::Result::<_>::from(response).map_err(Into::into)
+            })
+            .await
+    }
+
+    /// Sends a `res.php` action, for [`Protocol::V1`]
+    pub async fn legacy_action(
+        &self,
+        action: Action,
+        task_id: Option<TaskId>,
+    ) -> Result<res::Response<'static>> {
+        let request = res::Request {
+            key: task_id.map_or_else(|| Ok(self.keys.primary()), |id| self.keys.key_for(id))?,
+            action,
+            id: task_id.map(Into::into),
+            json: 1,
+        };
+
+        self.retry_policy
+            .run(|| async {
+                let response: res::Response = self.post_form("res.php", &request).await?;
+
+                error::Result::from(response)?
+                    .ok_or_else(|| error::ApiError::from_code(res::NOT_READY, None).into())
+            })
+            .await
+    }
+
+    /// Same as [`Api::send`], parsing the response
+    pub async fn post<R, S>(&self, method: &str, request: &R) -> Result<S>
+    where
+        R: Serialize + Sync,
+        S: DeserializeOwned,
+    {
+        let response = self.send(method, request).await?;
+
+        Ok(serde_json::from_slice(&response)?)
+    }
+}
+
+impl Outcome<'_> {
+    /// The cost of the task if it is solved
+    fn solved_cost(self) -> Option<Money> {
+        (self.status.as_deref() == Some("ready"))
+            .then_some(self.cost)
+            .flatten()
+    }
+}
+
+/// Encodes the fields of `request` as a form. Strings are sent as they are
+/// and every other value as its JSON representation
+fn form<R>(request: &R) -> Result<Vec<u8>>
+where
+    R: Serialize,
+{
+    let mut form = form_urlencoded::Serializer::new(String::new());
+
+    if let Value::Object(fields) = serde_json::to_value(request)? {
+        for (name, value) in fields {
+            match value {
+                Value::String(value) => form.append_pair(&name, &value),
+                Value::Null => &mut form,
+                value => form.append_pair(&name, &value.to_string()),
+            };
+        }
+    }
+
+    Ok(form.finish().into_bytes())
+}
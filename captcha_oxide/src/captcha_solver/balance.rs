@@ -0,0 +1,260 @@
+use std::{
+    sync::{Arc, Mutex, PoisonError, Weak},
+    time::Duration,
+};
+
+use tokio::{sync::watch, task::JoinHandle, time::sleep};
+
+use crate::Money;
+
+use super::api::Api;
+
+type Alert = (Money, Arc<dyn Fn(Money) + Send + Sync>);
+
+/// Settings for watching the balance of a [`crate::CaptchaSolver`] from a
+/// background task.
+///
+/// The balance of the account of the first API key is read every
+/// `interval`. The latest value is published through
+/// [`crate::CaptchaSolver::watch_balance`], callbacks can be run when it
+/// drops below a threshold, and new tasks can wait for the account to be
+/// topped up instead of failing with `ZeroBalance`.
+///
+/// The background task is started on the current tokio runtime the first
+/// time the balance is watched or a task is created.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use captcha_oxide::{CaptchaSolver, Money, captcha_solver::BalanceWatch};
+///
+/// let solver = CaptchaSolver::builder()
+///     .api_key("YOUR TWOCAPTCHA API KEY")
+///     .balance_watch(
+///         BalanceWatch::new(Duration::from_secs(60))
+///             .on_below(Money::from(5), |balance| println!("Only {balance} left"))
+///             .pause_below(Money::new(5, 1)),
+///     )
+///     .build();
+/// ```
+#[derive(Clone)]
+pub struct BalanceWatch {
+    interval: Duration,
+    alerts: Vec<Alert>,
+    pause_below: Option<Money>,
+}
+
+impl BalanceWatch {
+    /// Reads the balance every `interval`
+    #[must_use]
+    pub const fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            alerts: Vec::new(),
+            pause_below: None,
+        }
+    }
+
+    /// Calls `callback` with the balance when it drops below `threshold`.
+    /// It is called once every time the balance goes from at least
+    /// `threshold` to below it, including the first time it is read
+    #[must_use]
+    pub fn on_below<F>(mut self, threshold: Money, callback: F) -> Self
+    where
+        F: Fn(Money) + Send + Sync + 'static,
+    {
+        self.alerts.push((threshold, Arc::new(callback)));
+        self
+    }
+
+    /// Makes new tasks wait while the latest balance is below `minimum`,
+    /// until the account is topped up and the balance is read again.
+    /// Tasks are created as usual before the balance is first read
+    #[must_use]
+    pub const fn pause_below(mut self, minimum: Money) -> Self {
+        self.pause_below = Some(minimum);
+        self
+    }
+}
+
+/// Runs the background task configured with a [`BalanceWatch`]
+pub struct BalanceWatcher {
+    shared: Arc<Shared>,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+struct Shared {
+    api: Arc<Api>,
+    settings: BalanceWatch,
+    balance: watch::Sender<Option<Money>>,
+}
+
+impl BalanceWatcher {
+    pub fn new(api: Arc<Api>, settings: BalanceWatch) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                api,
+                settings,
+                balance: watch::Sender::new(None),
+            }),
+            task: Mutex::new(None),
+        }
+    }
+
+    /// Returns a receiver for the latest balance, starting the background
+    /// task if it is not running, e.g. because the runtime it was spawned
+    /// on has shut down
+    pub fn subscribe(&self) -> watch::Receiver<Option<Money>> {
+        let mut task = self.task.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if task.as_ref().map_or(true, JoinHandle::is_finished) {
+            *task = Some(tokio::spawn(run(Arc::downgrade(&self.shared))));
+        }
+        drop(task);
+
+        self.shared.balance.subscribe()
+    }
+
+    /// Waits until the latest balance is not below the minimum set with
+    /// [`BalanceWatch::pause_below`], starting the background task if it is
+    /// not running
+    pub async fn wait_until_funded(&self) {
+        let mut balance = self.subscribe();
+
+        let Some(minimum) = self.shared.settings.pause_below else {
+            return;
+        };
+
+        let _ = balance
+            .wait_for(|balance| balance.map_or(true, |balance| balance >= minimum))
+            .await;
+    }
+}
+
+/// The background task. Runs until the [`BalanceWatcher`] is dropped
+async fn run(shared: Weak<Shared>) {
+    let mut armed = Vec::new();
+
+    loop {
+        let Some(shared) = shared.upgrade() else {
+            return;
+        };
+
+        let alerts = &shared.settings.alerts;
+        armed.resize(alerts.len(), true);
+
+        if let Ok(balance) = shared.api.balance().await {
+            for ((threshold, callback), armed) in alerts.iter().zip(&mut armed) {
+                if balance >= *threshold {
+                    *armed = true;
+                } else if *armed {
+                    *armed = false;
+                    callback(balance);
+                }
+            }
+
+            shared.balance.send_replace(Some(balance));
+        }
+
+        let interval = shared.settings.interval;
+        drop(shared);
+
+        sleep(interval).await;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    use captcha_oxide_testkit::{MockServer, TaskScript};
+    use serde_json::json;
+    use tokio::time::timeout;
+
+    use super::BalanceWatch;
+    use crate::{mock, Money};
+
+    const INTERVAL: Duration = Duration::from_millis(20);
+
+    #[tokio::test]
+    async fn publishes_the_balance() {
+        let server = MockServer::start().await;
+        server.set_balance(3.5);
+
+        let solver = mock::builder(&server)
+            .balance_watch(BalanceWatch::new(INTERVAL))
+            .build();
+        let mut balance = solver.watch_balance().unwrap();
+
+        balance.wait_for(Option::is_some).await.unwrap();
+        assert_eq!(*balance.borrow(), Some(Money::new(35, 1)));
+
+        server.set_balance(2.0);
+        balance
+            .wait_for(|&balance| balance == Some(Money::from(2)))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn alerts_once_per_drop() {
+        let server = MockServer::start().await;
+        server.set_balance(1.0);
+
+        let alerts = Arc::new(AtomicUsize::new(0));
+        let counter = alerts.clone();
+
+        let solver = mock::builder(&server)
+            .balance_watch(
+                BalanceWatch::new(INTERVAL).on_below(Money::from(2), move |_| {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                }),
+            )
+            .build();
+        let mut balance = solver.watch_balance().unwrap();
+
+        balance.wait_for(Option::is_some).await.unwrap();
+        tokio::time::sleep(INTERVAL * 3).await;
+        assert_eq!(alerts.load(Ordering::SeqCst), 1);
+
+        server.set_balance(5.0);
+        balance
+            .wait_for(|&balance| balance == Some(Money::from(5)))
+            .await
+            .unwrap();
+        server.set_balance(0.5);
+        balance
+            .wait_for(|&balance| balance == Some(Money::new(5, 1)))
+            .await
+            .unwrap();
+        assert_eq!(alerts.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn pauses_submissions() {
+        let server = MockServer::start().await;
+        server.set_balance(0.1);
+        server.enqueue(TaskScript::new().ready(json!({ "text": "w68hp" })));
+
+        let solver = mock::builder(&server)
+            .balance_watch(BalanceWatch::new(INTERVAL).pause_below(Money::from(1)))
+            .build();
+        let captcha = mock::captcha();
+
+        let mut balance = solver.watch_balance().unwrap();
+        balance.wait_for(Option::is_some).await.unwrap();
+
+        let paused = timeout(INTERVAL * 5, solver.solve(&captcha)).await;
+        assert!(paused.is_err());
+        assert!(server.tasks().is_empty());
+
+        server.set_balance(10.0);
+        assert!(solver.solve(&captcha).await.is_ok());
+    }
+}
@@ -0,0 +1,113 @@
+use std::sync::Arc;
+
+use futures_util::{stream, Stream, StreamExt};
+
+use crate::{captcha::solution::Solution, Captcha, CaptchaSolver, Result};
+
+use super::{budget::Job, SolveOptions};
+
+/// See [`CaptchaSolver::solve_many`]. The whole batch is a single job of the
+/// solver's [`super::Budget`].
+///
+/// The tasks are waited on through the solver's [`super::poller::Poller`],
+/// like any other task, rather than with a loop of their own. Their polls
+/// cannot be merged into fewer requests: `getTaskResult` takes a single
+/// task id, and the `ids` lookup of `res.php` only returns the answers,
+/// without the cost and timestamps a [`Solution`] is made of
+pub fn solve_many<'s, 'a, T, I>(
+    solver: &'s CaptchaSolver,
+    tasks: I,
+    concurrency: usize,
+    options: SolveOptions,
+) -> impl Stream<Item = (usize, Result<Solution<'a, T>>)> + 's
+where
+    T: Captcha + 's,
+    I: IntoIterator<Item = &'s T>,
+    I::IntoIter: Send + 's,
+    'a: 's,
+{
+    let options = options.or(solver.solve_options);
+    let job = Arc::new(Job::default());
+
+    stream::iter(tasks.into_iter().enumerate())
+        .map(move |(index, task)| {
+            let job = job.clone();
+            async move { (index, solver.solve_until(task, options, None, &job).await) }
+        })
+        .buffer_unordered(concurrency.max(1))
+}
+
+#[cfg(test)]
+mod test {
+    use captcha_oxide_testkit::{MockServer, TaskScript};
+    use futures_util::StreamExt;
+    use serde_json::json;
+
+    use crate::mock;
+
+    fn solved(polls: usize, text: &str) -> TaskScript {
+        TaskScript::new()
+            .processing(polls)
+            .ready(json!({ "text": text }))
+    }
+
+    #[tokio::test]
+    async fn completion_order() {
+        let server = MockServer::start().await;
+        server.enqueue(solved(4, "slow"));
+        server.enqueue(solved(0, "fast"));
+        server.enqueue(solved(2, "medium"));
+
+        let solver = mock::solver(&server);
+        let captchas: Vec<_> = (0..3).map(|_| mock::captcha()).collect();
+
+        let results: Vec<_> = solver
+            .solve_many(&captchas, 3)
+            .map(|(index, solution)| (index, solution.unwrap().solution.text.into_owned()))
+            .collect()
+            .await;
+
+        let (mut indices, texts): (Vec<_>, Vec<_>) = results.into_iter().unzip();
+        indices.sort_unstable();
+
+        assert_eq!(texts, ["fast", "medium", "slow"]);
+        assert_eq!(indices, [0, 1, 2]);
+    }
+
+    #[tokio::test]
+    async fn bounded_concurrency() {
+        let server = MockServer::start().await;
+        for _ in 0..5 {
+            server.enqueue(solved(1, "w68hp"));
+        }
+
+        let solver = mock::solver(&server);
+        let captchas: Vec<_> = (0..5).map(|_| mock::captcha()).collect();
+
+        let mut stream = Box::pin(solver.solve_many(&captchas, 2));
+
+        assert!(stream.next().await.unwrap().1.is_ok());
+        assert_eq!(server.tasks().len(), 2);
+
+        let rest: Vec<_> = stream.collect().await;
+
+        assert_eq!(rest.len(), 4);
+        assert_eq!(server.tasks().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn reports_errors_with_their_index() {
+        let server = MockServer::start().await;
+        server.enqueue(solved(0, "w68hp"));
+        server.enqueue(TaskScript::rejected("ERROR_ZERO_CAPTCHA_FILESIZE"));
+
+        let solver = mock::solver(&server);
+        let captchas: Vec<_> = (0..2).map(|_| mock::captcha()).collect();
+
+        let mut results: Vec<_> = solver.solve_many(&captchas, 1).collect().await;
+        results.sort_by_key(|(index, _)| *index);
+
+        assert!(results[0].1.is_ok());
+        assert!(results[1].1.is_err());
+    }
+}
@@ -0,0 +1,353 @@
+use std::{
+    fmt::Display,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, PoisonError,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{Error, Money, Result};
+
+use super::{event::EventHandler, Event};
+
+const SECONDS_PER_HOUR: u64 = 60 * 60;
+const SECONDS_PER_DAY: u64 = 24 * SECONDS_PER_HOUR;
+
+/// Caps on how much a [`crate::CaptchaSolver`] spends, so a runaway loop
+/// cannot drain the account.
+///
+/// The cost of every solved task is added to the spend of the current hour
+/// and day, in UTC, and of the job it belongs to. A job is a single call to
+/// one of the `solve` methods, or a whole batch solved with
+/// [`crate::CaptchaSolver::solve_many`].
+///
+/// The limits are checked before each task is created, and tasks are
+/// refused with [`Error::BudgetExceeded`] once the spend reached one of
+/// them. As the cost of a task is only known once it is solved, the tasks
+/// that are in flight when a limit is reached can take the spend past it.
+///
+/// # Example
+/// ```
+/// use captcha_oxide::{CaptchaSolver, Money, captcha_solver::{Budget, Event}};
+///
+/// let solver = CaptchaSolver::builder()
+///     .api_key("YOUR TWOCAPTCHA API KEY")
+///     .budget(
+///         Budget::new()
+///             .per_hour(Money::new(5, 1))
+///             .per_day(Money::from(5))
+///             .soft_limit(80),
+///     )
+///     .on_event(|event| {
+///         if let Event::SoftLimitReached { period, spent, .. } = event {
+///             println!("Spent {spent} this {period}");
+///         }
+///     })
+///     .build();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Budget {
+    per_hour: Option<Money>,
+    per_day: Option<Money>,
+    per_job: Option<Money>,
+    soft_limit: Option<u8>,
+}
+
+/// The period a [`Budget`] limit applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum BudgetPeriod {
+    Hour,
+    Day,
+    Job,
+}
+
+impl Display for BudgetPeriod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Hour => "hour",
+            Self::Day => "day",
+            Self::Job => "job",
+        })
+    }
+}
+
+impl Budget {
+    /// A budget with no limits
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            per_hour: None,
+            per_day: None,
+            per_job: None,
+            soft_limit: None,
+        }
+    }
+
+    /// Caps the spend of every hour, in UTC
+    #[must_use]
+    pub const fn per_hour(mut self, limit: Money) -> Self {
+        self.per_hour = Some(limit);
+        self
+    }
+
+    /// Caps the spend of every day, in UTC
+    #[must_use]
+    pub const fn per_day(mut self, limit: Money) -> Self {
+        self.per_day = Some(limit);
+        self
+    }
+
+    /// Caps the spend of every job
+    #[must_use]
+    pub const fn per_job(mut self, limit: Money) -> Self {
+        self.per_job = Some(limit);
+        self
+    }
+
+    /// Emits an [`Event::SoftLimitReached`] the first time the spend of a
+    /// period reaches `percent` percent of its limit
+    #[must_use]
+    pub const fn soft_limit(mut self, percent: u8) -> Self {
+        self.soft_limit = Some(percent);
+        self
+    }
+
+    const fn limit(&self, period: BudgetPeriod) -> Option<Money> {
+        match period {
+            BudgetPeriod::Hour => self.per_hour,
+            BudgetPeriod::Day => self.per_day,
+            BudgetPeriod::Job => self.per_job,
+        }
+    }
+}
+
+/// Tracks the spend of a [`crate::CaptchaSolver`] against its [`Budget`]
+pub struct BudgetTracker {
+    budget: Budget,
+    hour: Mutex<Window>,
+    day: Mutex<Window>,
+    on_event: Option<EventHandler>,
+}
+
+/// The spend of a single job, and whether any task was created for it
+#[derive(Default)]
+pub struct Job {
+    spent: Mutex<Window>,
+    created: AtomicBool,
+}
+
+#[derive(Default)]
+struct Window {
+    /// The hour or day, counted from the Unix epoch, the spend is for
+    index: u64,
+    spent: Money,
+    warned: bool,
+}
+
+impl Window {
+    /// Forgets the spend of a previous window
+    fn roll_over(&mut self, index: u64) {
+        if self.index != index {
+            *self = Self {
+                index,
+                ..Self::default()
+            };
+        }
+    }
+}
+
+impl Job {
+    /// Records that a task was created for the job
+    pub fn created(&self) {
+        self.created.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether a task was created for the job, even if it failed later
+    pub fn has_task(&self) -> bool {
+        self.created.load(Ordering::Relaxed)
+    }
+}
+
+impl BudgetTracker {
+    pub fn new(budget: Budget, on_event: Option<EventHandler>) -> Self {
+        Self {
+            budget,
+            hour: Mutex::default(),
+            day: Mutex::default(),
+            on_event,
+        }
+    }
+
+    /// Checks that no limit was reached before creating a task for `job`
+    ///
+    /// # Errors
+    /// Returns [`Error::BudgetExceeded`] with the period of the first limit
+    /// that was reached
+    pub fn check(&self, job: &Job) -> Result<()> {
+        let now = now();
+
+        for (period, window, index) in [
+            (BudgetPeriod::Hour, &self.hour, now / SECONDS_PER_HOUR),
+            (BudgetPeriod::Day, &self.day, now / SECONDS_PER_DAY),
+            (BudgetPeriod::Job, &job.spent, 0),
+        ] {
+            let Some(limit) = self.budget.limit(period) else {
+                continue;
+            };
+
+            let mut window = lock(window);
+            window.roll_over(index);
+
+            if window.spent >= limit {
+                return Err(Error::BudgetExceeded { period });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds the cost of a solved task to the spend of the current hour and day
+    pub fn record(&self, cost: Money) {
+        let now = now();
+
+        self.add(BudgetPeriod::Hour, &self.hour, now / SECONDS_PER_HOUR, cost);
+        self.add(BudgetPeriod::Day, &self.day, now / SECONDS_PER_DAY, cost);
+    }
+
+    /// Adds the cost of a task solved for `job` to the job's spend
+    pub fn record_job(&self, job: &Job, cost: Money) {
+        self.add(BudgetPeriod::Job, &job.spent, 0, cost);
+    }
+
+    fn add(&self, period: BudgetPeriod, window: &Mutex<Window>, index: u64, cost: Money) {
+        let mut window = lock(window);
+        window.roll_over(index);
+        window.spent += cost;
+
+        let (Some(limit), Some(percent)) = (self.budget.limit(period), self.budget.soft_limit)
+        else {
+            return;
+        };
+
+        if window.warned || window.spent * 100 < limit * u32::from(percent) {
+            return;
+        }
+
+        window.warned = true;
+        let spent = window.spent;
+        drop(window);
+
+        if let Some(on_event) = &self.on_event {
+            on_event(&Event::SoftLimitReached {
+                period,
+                spent,
+                limit,
+            });
+        }
+    }
+}
+
+fn lock(window: &Mutex<Window>) -> std::sync::MutexGuard<'_, Window> {
+    window.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs())
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use captcha_oxide_testkit::{MockServer, TaskScript};
+    use futures_util::StreamExt;
+    use serde_json::json;
+
+    use super::{Budget, BudgetPeriod};
+    use crate::{captcha_solver::Event, mock, Error, Money};
+
+    fn costing(cost: &str) -> TaskScript {
+        TaskScript::new()
+            .ready(json!({ "text": "w68hp" }))
+            .cost(cost)
+    }
+
+    #[tokio::test]
+    async fn refuses_tasks_past_the_limit() {
+        let server = MockServer::start().await;
+        server.enqueue(costing("0.001"));
+        server.enqueue(costing("0.001"));
+
+        let solver = mock::builder(&server)
+            .budget(Budget::new().per_day(Money::new(2, 3)))
+            .build();
+
+        for _ in 0..2 {
+            let _ = mock::solved(solver.solve(&mock::captcha()).await).unwrap();
+        }
+        let error = solver.solve(&mock::captcha()).await.unwrap_err();
+
+        assert!(matches!(
+            error,
+            Error::BudgetExceeded {
+                period: BudgetPeriod::Day
+            }
+        ));
+        assert_eq!(server.tasks().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn caps_every_job() {
+        let server = MockServer::start().await;
+        for _ in 0..3 {
+            server.enqueue(costing("0.001"));
+        }
+
+        let solver = mock::builder(&server)
+            .budget(Budget::new().per_job(Money::new(2, 3)))
+            .build();
+        let captchas = [mock::captcha(), mock::captcha(), mock::captcha()];
+
+        let results: Vec<_> = solver.solve_many(&captchas, 1).collect().await;
+        let refused = results
+            .iter()
+            .filter(|(_, result)| matches!(result, Err(Error::BudgetExceeded { .. })))
+            .count();
+
+        assert_eq!(refused, 1);
+        assert!(solver.solve(&mock::captcha()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn fires_the_soft_limit_once() {
+        let server = MockServer::start().await;
+        for _ in 0..3 {
+            server.enqueue(costing("0.001"));
+        }
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = events.clone();
+
+        let solver = mock::builder(&server)
+            .budget(Budget::new().per_hour(Money::new(4, 3)).soft_limit(50))
+            .on_event(move |event| recorded.lock().unwrap().push(event.clone()))
+            .build();
+
+        for _ in 0..3 {
+            let _ = mock::solved(solver.solve(&mock::captcha()).await).unwrap();
+        }
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            [Event::SoftLimitReached {
+                period: BudgetPeriod::Hour,
+                spent: Money::new(2, 3),
+                limit: Money::new(4, 3),
+            }]
+        );
+    }
+}
@@ -1,30 +1,116 @@
 #![allow(clippy::module_name_repetitions)]
 
-#[cfg(feature = "callback")]
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
 use url::Url;
 
-use crate::{language_pool::LanguagePool, CaptchaSolver};
+use crate::{
+    language_pool::LanguagePool,
+    transport::{self, cassette::Recorder, Transport},
+    CaptchaSolver,
+};
+
+#[cfg(feature = "pingback")]
+use super::PingbackReceiver;
+use super::{
+    api::Api, balance::BalanceWatcher, budget::BudgetTracker, event::EventHandler,
+    key_pool::KeyPool, poller::Poller, rate_limit::RateLimit, stats::StatsCollector, BalanceWatch,
+    Budget, Event, KeyConfig, Protocol, Provider, RetryPolicy, SolveOptions,
+};
 
 pub struct MissingApiKey;
-pub struct ApiKey(Box<str>);
+pub struct ApiKey(Vec<KeyConfig>);
 
 pub struct CaptchaSolverBuilder<T> {
     api_key: T,
     language_pool: LanguagePool,
+    api_url: Option<Url>,
+    legacy_api_url: Option<Url>,
+    protocol: Protocol,
+    provider: Option<Provider>,
+    transport: Option<Arc<dyn Transport>>,
+    record_to: Option<PathBuf>,
+    retry_policy: Option<RetryPolicy>,
+    rate_limit: Option<RateLimit>,
+    budget: Option<Budget>,
+    balance_watch: Option<BalanceWatch>,
+    solve_options: SolveOptions,
+    on_event: Option<EventHandler>,
 
     #[cfg(feature = "callback")]
     callback_url: Option<Url>,
+
+    #[cfg(feature = "pingback")]
+    pingback: Option<PingbackReceiver>,
 }
 
 impl CaptchaSolverBuilder<ApiKey> {
+    /// Replaces the API keys set so far with `api_key`
+    #[must_use = "A builder type must have its `build` method called to build the target type"]
+    pub fn api_key<T>(mut self, api_key: T) -> Self
+    where
+        T: Into<KeyConfig>,
+    {
+        self.api_key = ApiKey(vec![api_key.into()]);
+        self
+    }
+
+    /// Adds another API key to rotate through. Tasks are created with each
+    /// key in turn, skipping the ones that are at their concurrency cap or
+    /// spend limit, and are polled and reported with the key that created
+    /// them. Requests that are not about a task, such as getting the
+    /// balance, use the first key
+    #[must_use = "A builder type must have its `build` method called to build the target type"]
+    pub fn add_api_key<T>(mut self, api_key: T) -> Self
+    where
+        T: Into<KeyConfig>,
+    {
+        self.api_key.0.push(api_key.into());
+        self
+    }
+
     #[must_use]
     pub fn build(self) -> CaptchaSolver {
+        let mut transport = self.transport.unwrap_or_else(transport::default);
+        if let Some(path) = self.record_to {
+            transport = Arc::new(Recorder::new(transport, path));
+        }
+
+        let provider = self.provider.unwrap_or_default();
+        let (api_url, legacy_api_url) = provider.urls();
+
+        let api = Arc::new(Api {
+            keys: KeyPool::new(self.api_key.0),
+            url: self.api_url.unwrap_or_else(|| api_url.clone()),
+            legacy_url: self
+                .legacy_api_url
+                .unwrap_or_else(|| legacy_api_url.clone()),
+            protocol: self.protocol,
+            provider,
+            transport,
+            retry_policy: self.retry_policy.unwrap_or_default(),
+            rate_limit: self.rate_limit,
+            budget: self
+                .budget
+                .map(|budget| BudgetTracker::new(budget, self.on_event.clone())),
+        });
+
         CaptchaSolver {
-            api_key: self.api_key.0,
+            poller: Poller::new(api.clone()),
+            balance_watcher: self
+                .balance_watch
+                .map(|settings| BalanceWatcher::new(api.clone(), settings)),
+            stats: StatsCollector::default(),
+            api,
             language_pool: self.language_pool,
+            solve_options: self.solve_options,
+            on_event: self.on_event,
 
             #[cfg(feature = "callback")]
             callback_url: self.callback_url,
+
+            #[cfg(feature = "pingback")]
+            pingback: self.pingback,
         }
     }
 }
@@ -35,23 +121,54 @@ impl CaptchaSolverBuilder<MissingApiKey> {
         Self {
             api_key: MissingApiKey,
             language_pool: LanguagePool::En,
+            api_url: None,
+            legacy_api_url: None,
+            protocol: Protocol::V2,
+            provider: None,
+            transport: None,
+            record_to: None,
+            retry_policy: None,
+            rate_limit: None,
+            budget: None,
+            balance_watch: None,
+            solve_options: SolveOptions::new(),
+            on_event: None,
 
             #[cfg(feature = "callback")]
             callback_url: None,
+
+            #[cfg(feature = "pingback")]
+            pingback: None,
         }
     }
 
+    /// Sets the API key, which can be a [`KeyConfig`] to limit its use
     #[must_use = "A builder type must have its `build` method called to build the target type"]
     pub fn api_key<T>(self, api_key: T) -> CaptchaSolverBuilder<ApiKey>
     where
-        T: Into<Box<str>>,
+        T: Into<KeyConfig>,
     {
         CaptchaSolverBuilder {
-            api_key: ApiKey(api_key.into()),
+            api_key: ApiKey(vec![api_key.into()]),
             language_pool: self.language_pool,
+            api_url: self.api_url,
+            legacy_api_url: self.legacy_api_url,
+            protocol: self.protocol,
+            provider: self.provider,
+            transport: self.transport,
+            record_to: self.record_to,
+            retry_policy: self.retry_policy,
+            rate_limit: self.rate_limit,
+            budget: self.budget,
+            balance_watch: self.balance_watch,
+            solve_options: self.solve_options,
+            on_event: self.on_event,
 
             #[cfg(feature = "callback")]
             callback_url: self.callback_url,
+
+            #[cfg(feature = "pingback")]
+            pingback: self.pingback,
         }
     }
 }
@@ -63,6 +180,125 @@ impl<T> CaptchaSolverBuilder<T> {
         self
     }
 
+    /// Sets the base URL all API methods are resolved against. Defaults to
+    /// the URL of the [`Provider`], `https://api.2captcha.com/`. A trailing
+    /// slash is added to the path if it is missing, so
+    /// `http://gateway/2captcha` resolves `createTask` to
+    /// `http://gateway/2captcha/createTask`
+    #[must_use = "A builder type must have its `build` method called to build the target type"]
+    pub fn api_url(mut self, api_url: Url) -> Self {
+        self.api_url = Some(with_trailing_slash(api_url));
+        self
+    }
+
+    /// Sets the base URL of the legacy API, used for the few methods that
+    /// are only available through `res.php`, such as managing pingback
+    /// addresses, and for everything else with [`Protocol::V1`]. Defaults to
+    /// the legacy URL of the [`Provider`], `https://2captcha.com/`. A
+    /// trailing slash is added the same way as in
+    /// [`CaptchaSolverBuilder::api_url`]
+    #[must_use = "A builder type must have its `build` method called to build the target type"]
+    pub fn legacy_api_url(mut self, legacy_api_url: Url) -> Self {
+        self.legacy_api_url = Some(with_trailing_slash(legacy_api_url));
+        self
+    }
+
+    /// Sets the [`Provider`] tasks are sent to. Defaults to
+    /// [`Provider::two_captcha`]. The URLs set with
+    /// [`CaptchaSolverBuilder::api_url`] and
+    /// [`CaptchaSolverBuilder::legacy_api_url`] take precedence over the
+    /// provider's
+    #[must_use = "A builder type must have its `build` method called to build the target type"]
+    pub fn provider(mut self, provider: Provider) -> Self {
+        self.provider = Some(provider);
+        self
+    }
+
+    /// Sets the version of the API tasks are created, polled and reported
+    /// through. Defaults to [`Protocol::V2`]
+    #[must_use = "A builder type must have its `build` method called to build the target type"]
+    pub const fn protocol(mut self, protocol: Protocol) -> Self {
+        self.protocol = protocol;
+        self
+    }
+
+    /// Sets the [`Transport`] used to talk to the API. The clients of the
+    /// enabled HTTP backends implement [`Transport`], so you can pass in one
+    /// configured with its own timeouts, a proxy for the API connection, TLS
+    /// settings or user agent
+    #[must_use = "A builder type must have its `build` method called to build the target type"]
+    pub fn transport<U>(mut self, transport: U) -> Self
+    where
+        U: Transport + 'static,
+    {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Records every exchange with the API to a cassette at `path`, with
+    /// the API key redacted, so it can be replayed later with
+    /// [`transport::cassette::Replay`]. The file is replaced if it exists
+    #[must_use = "A builder type must have its `build` method called to build the target type"]
+    pub fn record_to<P>(mut self, path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        self.record_to = Some(path.into());
+        self
+    }
+
+    /// Sets the [`RetryPolicy`] applied to every request sent to the API.
+    /// Defaults to [`RetryPolicy::default`]
+    #[must_use = "A builder type must have its `build` method called to build the target type"]
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    /// Caps the requests sent to the API, including the polls for every
+    /// pending task, to `requests` per `period`. Requests are spaced out
+    /// evenly and wait for their turn instead of failing. There is no cap
+    /// by default
+    #[must_use = "A builder type must have its `build` method called to build the target type"]
+    pub fn rate_limit(mut self, requests: u32, period: Duration) -> Self {
+        self.rate_limit = Some(RateLimit::new(requests, period));
+        self
+    }
+
+    /// Caps the spend of the solver with `budget`. There is no cap by
+    /// default
+    #[must_use = "A builder type must have its `build` method called to build the target type"]
+    pub const fn budget(mut self, budget: Budget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Watches the balance from a background task, see [`BalanceWatch`]
+    #[must_use = "A builder type must have its `build` method called to build the target type"]
+    pub fn balance_watch(mut self, balance_watch: BalanceWatch) -> Self {
+        self.balance_watch = Some(balance_watch);
+        self
+    }
+
+    /// Sets the default [`SolveOptions`] used when solving tasks. They can be
+    /// overridden for a single task with [`CaptchaSolver::solve_with`]
+    #[must_use = "A builder type must have its `build` method called to build the target type"]
+    pub const fn solve_options(mut self, solve_options: SolveOptions) -> Self {
+        self.solve_options = solve_options;
+        self
+    }
+
+    /// Sets a function that is called with every [`Event`] the solver
+    /// emits, e.g. to account for tasks that were paid for but abandoned
+    #[must_use = "A builder type must have its `build` method called to build the target type"]
+    pub fn on_event<F>(mut self, on_event: F) -> Self
+    where
+        F: Fn(&Event) + Send + Sync + 'static,
+    {
+        self.on_event = Some(Arc::new(on_event));
+        self
+    }
+
     #[cfg(feature = "callback")]
     #[must_use = "A builder type must have its `build` method called to build the target type"]
     pub fn callback_url(mut self, callback_url: Url) -> Self {
@@ -76,6 +312,22 @@ impl<T> CaptchaSolverBuilder<T> {
         self.callback_url = None;
         self
     }
+
+    /// Sets `callback_url` as the callback URL and waits for the pingbacks
+    /// sent to it with `receiver`, so solving returns the solution as soon
+    /// as it is ready instead of `Ok(None)`. The tasks are still polled in
+    /// case a pingback is lost.
+    ///
+    /// `callback_url` is the public address 2captcha sends pingbacks to,
+    /// which must reach `receiver` and be registered with
+    /// [`CaptchaSolver::add_pingback`]
+    #[cfg(feature = "pingback")]
+    #[must_use = "A builder type must have its `build` method called to build the target type"]
+    pub fn pingback(mut self, receiver: PingbackReceiver, callback_url: Url) -> Self {
+        self.pingback = Some(receiver);
+        self.callback_url = Some(callback_url);
+        self
+    }
 }
 
 impl Default for CaptchaSolverBuilder<MissingApiKey> {
@@ -83,3 +335,14 @@ impl Default for CaptchaSolverBuilder<MissingApiKey> {
         Self::new()
     }
 }
+
+/// Adds a trailing slash to the path of `url`, so the API methods are
+/// joined to it instead of replacing its last segment
+pub(super) fn with_trailing_slash(mut url: Url) -> Url {
+    if !url.path().ends_with('/') {
+        let path = format!("{}/", url.path());
+        url.set_path(&path);
+    }
+
+    url
+}
@@ -2,13 +2,151 @@ use thiserror::Error;
 
 use crate::{
     captcha::{solution::Solution, Captcha},
-    two_captcha::{create_task, get_balance, get_task_result, report},
+    two_captcha::{create_task, get_balance, get_task_result, pingback_addresses, report},
+    two_captcha_v1::{res, submit, Status},
+    Money,
 };
 
-pub type Result<T> = core::result::Result<T, Error>;
+pub type Result<T> = core::result::Result<T, ApiError>;
+
+/// The code given to errors the API reported without an error code
+pub const UNKNOWN_CODE: &str = "ERROR_UNKNOWN";
+
+/// An error returned by the 2captcha API, along with the `errorId` and
+/// `errorDescription` it was sent with
+#[derive(Error, Debug)]
+#[error("{kind}")]
+pub struct ApiError {
+    /// What went wrong
+    pub kind: Error,
+
+    /// The numeric `errorId` of the error. This is not present when the
+    /// error was built from an error code alone
+    pub error_id: Option<u16>,
+
+    /// The human readable `errorDescription` returned by the API, if any
+    pub description: Option<Box<str>>,
+}
+
+impl ApiError {
+    /// Builds an [`ApiError`] from the fields of an API error response,
+    /// mapping `error_code` to the matching [`Error`] variant
+    #[must_use]
+    pub fn new(error_code: &str, error_id: u16, description: Option<&str>) -> Self {
+        let kind = match Error::from(error_code) {
+            Error::Unknown { code, .. } => Error::Unknown {
+                code,
+                description: description.map(Into::into),
+                error_id: Some(error_id),
+            },
+            kind => kind,
+        };
+
+        Self {
+            kind,
+            error_id: Some(error_id),
+            description: description.map(Into::into),
+        }
+    }
+
+    /// Builds an [`ApiError`] from an error code returned by the legacy API,
+    /// which does not send an `errorId`
+    #[must_use]
+    pub fn from_code(error_code: &str, description: Option<&str>) -> Self {
+        let kind = match Error::from(error_code) {
+            Error::Unknown { code, .. } => Error::Unknown {
+                code,
+                description: description.map(Into::into),
+                error_id: None,
+            },
+            kind => kind,
+        };
+
+        Self {
+            kind,
+            error_id: None,
+            description: description.map(Into::into),
+        }
+    }
+
+    /// The error code returned by the API, e.g. `ERROR_ZERO_BALANCE`
+    #[must_use]
+    pub fn code(&self) -> &str {
+        self.kind.code()
+    }
+
+    /// The [`ErrorClass`] of this error's [`Error`] kind
+    #[must_use]
+    pub const fn class(&self) -> ErrorClass {
+        self.kind.class()
+    }
+}
+
+/// Broad categories of errors, meant to help you decide what to do after a
+/// request fails without having to match on every variant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorClass {
+    /// The request did not reach the API or its response could not be read.
+    /// Sending the same request again may succeed
+    Transient,
+
+    /// The API is temporarily refusing requests from you. Sending the same
+    /// request again after backing off may succeed
+    Throttled,
+
+    /// The proxy sent along with the task could not be used. Resubmitting
+    /// the task with a different proxy may succeed
+    Proxy,
+
+    /// The task data is invalid. Resubmitting the same task will fail again
+    TaskData,
+
+    /// Workers could not solve the captcha. Resubmitting the task may succeed
+    Unsolvable,
+
+    /// The API key or the account it belongs to can not be used until the
+    /// issue is fixed in the dashboard
+    Account,
+
+    /// The request itself is invalid, e.g. it refers to a task that does not
+    /// exist. Sending it again will fail again
+    Request,
+
+    /// The API returned an error code that is not known by this library
+    Unknown,
+}
+
+impl ErrorClass {
+    /// Whether sending the exact same request again may succeed
+    #[must_use]
+    pub const fn is_retryable(self) -> bool {
+        matches!(self, Self::Transient | Self::Throttled)
+    }
+}
+
+impl From<Error> for ApiError {
+    fn from(kind: Error) -> Self {
+        let (error_id, description) = match kind {
+            Error::Unknown {
+                error_id,
+                ref description,
+                ..
+            } => (error_id, description.clone()),
+            _ => (None, None),
+        };
+
+        Self {
+            kind,
+            error_id,
+            description,
+        }
+    }
+}
 
 /// Represents all the errors that can be returned by the 2captcha API
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum Error {
     #[error("Your API key is incorrect. Make sure you set the key correctly and copied it from the dashboard in Customer or Developer mode")]
     InvalidApiKey,
@@ -43,6 +181,11 @@ pub enum Error {
     #[error("You've provided an incorrect captcha ID in the request")]
     CaptchaIdNotFound,
 
+    #[error(
+        "The captcha ID provided in the request is malformed or does not belong to your account"
+    )]
+    WrongCaptchaId,
+
     #[error("Your IP address is banned due to improper use of the API")]
     IpBlocked,
 
@@ -69,12 +212,55 @@ pub enum Error {
 
     #[error("The error is returned in cases when `imgInstructions` contains an unsupported file type, corrupted file or the size of the image is over the limits. The limits are described in the corresponding task type specification.")]
     BadImageInstructions,
+
+    #[error("The `websiteURL` parameter is missing or has an invalid format")]
+    InvalidPageUrl,
+
+    #[error("The `action` parameter is missing or empty")]
+    EmptyAction,
+
+    #[error("The token provided in the request has expired")]
+    TokenExpired,
+
+    #[error("The captcha image was blocked by the target website")]
+    CaptchaImageBlocked,
+
+    #[error("The token or website URL provided in the request is not valid")]
+    BadTokenOrPageUrl,
+
+    #[error("You are sending too many requests and have been temporarily rate limited")]
+    TooManyRequests,
+
+    #[error("The file you uploaded has an unsupported extension")]
+    UnsupportedFileExtension,
+
+    #[error("The server could not process the uploaded file. Make sure it was encoded properly")]
+    UploadFailed,
+
+    #[error("The proxy information provided in the request has an invalid format")]
+    BadProxyFormat,
+
+    #[error("The IP address provided in the request is not valid")]
+    InvalidIpAddress,
+
+    #[error("The report was not recorded, either because it was sent too late or because you have reported too many solutions recently")]
+    ReportNotRecorded,
+
+    #[error("You have already reported this solution")]
+    DuplicateReport,
+
+    #[error("The API returned an error code that is not known by this library: {code}")]
+    Unknown {
+        code: Box<str>,
+        description: Option<Box<str>>,
+        error_id: Option<u16>,
+    },
 }
 
 impl From<&str> for Error {
     fn from(value: &str) -> Self {
         match value {
-            "ERROR_KEY_DOES_NOT_EXIST" => Self::InvalidApiKey,
+            "ERROR_KEY_DOES_NOT_EXIST" | "ERROR_WRONG_USER_KEY" => Self::InvalidApiKey,
             "ERROR_NO_SLOT_AVAILABLE" => Self::NoSlotAvailable,
             "ERROR_ZERO_CAPTCHA_FILESIZE" => Self::ImageTooSmall,
             "ERROR_TOO_BIG_CAPTCHA_FILESIZE" => Self::ImageTooBig,
@@ -85,10 +271,13 @@ impl From<&str> for Error {
             "ERROR_NO_SUCH_METHOD" => Self::NoSuchMethod,
             "ERROR_IMAGE_TYPE_NOT_SUPPORTED" => Self::UnsupportedImageType,
             "ERROR_NO_SUCH_CAPCHA_ID" => Self::CaptchaIdNotFound,
-            "ERROR_IP_BLOCKED" => Self::IpBlocked,
+            "ERROR_WRONG_CAPTCHA_ID" | "ERROR_WRONG_ID_FORMAT" => Self::WrongCaptchaId,
+            "ERROR_IP_BLOCKED" | "IP_BANNED" => Self::IpBlocked,
             "ERROR_TASK_ABSENT" => Self::TaskNotProvided,
             "ERROR_TASK_NOT_SUPPORTED" => Self::TaskNotSupported,
-            "ERROR_RECAPTCHA_INVALID_SITEKEY" => Self::InvalidSiteKey,
+            "ERROR_RECAPTCHA_INVALID_SITEKEY" | "ERROR_GOOGLEKEY" | "ERROR_SITEKEY" => {
+                Self::InvalidSiteKey
+            }
             "ERROR_ACCOUNT_SUSPENDED" => Self::AccountSuspended,
             "ERROR_BAD_PROXY" => Self::BadProxy,
             "ERROR_PROXY_CONNECTION_FAILED" | "ERR_PROXY_CONNECTION_FAILED" => {
@@ -96,7 +285,114 @@ impl From<&str> for Error {
             }
             "ERROR_BAD_PARAMETERS" => Self::BadParameters,
             "ERROR_BAD_IMGINSTRUCTIONS" => Self::BadImageInstructions,
-            x => unreachable!("Unreachable 2captcha error: {}", x),
+            "ERROR_PAGEURL" => Self::InvalidPageUrl,
+            "ERROR_EMPTY_ACTION" => Self::EmptyAction,
+            "ERROR_TOKEN_EXPIRED" => Self::TokenExpired,
+            "ERROR_CAPTCHAIMAGE_BLOCKED" => Self::CaptchaImageBlocked,
+            "ERROR_BAD_TOKEN_OR_PAGEURL" => Self::BadTokenOrPageUrl,
+            "ERROR_TOO_MUCH_REQUESTS" | "MAX_USER_TURN" => Self::TooManyRequests,
+            "ERROR_WRONG_FILE_EXTENSION" => Self::UnsupportedFileExtension,
+            "ERROR_UPLOAD" => Self::UploadFailed,
+            "ERROR_PROXY_FORMAT" => Self::BadProxyFormat,
+            "ERROR_IP_ADDRES" => Self::InvalidIpAddress,
+            "ERROR_REPORT_NOT_RECORDED" => Self::ReportNotRecorded,
+            "ERROR_DUPLICATE_REPORT" => Self::DuplicateReport,
+            "" => Self::Unknown {
+                code: UNKNOWN_CODE.into(),
+                description: None,
+                error_id: None,
+            },
+            code => Self::Unknown {
+                code: code.into(),
+                description: None,
+                error_id: None,
+            },
+        }
+    }
+}
+
+impl Error {
+    /// The canonical error code of this error, e.g. `ERROR_ZERO_BALANCE`.
+    /// For [`Error::Unknown`] this is the code exactly as it was returned, or
+    /// [`UNKNOWN_CODE`] if none was
+    #[must_use]
+    pub fn code(&self) -> &str {
+        match self {
+            Self::InvalidApiKey => "ERROR_KEY_DOES_NOT_EXIST",
+            Self::NoSlotAvailable => "ERROR_NO_SLOT_AVAILABLE",
+            Self::ImageTooSmall => "ERROR_ZERO_CAPTCHA_FILESIZE",
+            Self::ImageTooBig => "ERROR_TOO_BIG_CAPTCHA_FILESIZE",
+            Self::ZeroBalance => "ERROR_ZERO_BALANCE",
+            Self::IpNotAllowed => "ERROR_IP_NOT_ALLOWED",
+            Self::UnsolvableCaptcha => "ERROR_CAPTCHA_UNSOLVABLE",
+            Self::BadDuplicates => "ERROR_BAD_DUPLICATES",
+            Self::NoSuchMethod => "ERROR_NO_SUCH_METHOD",
+            Self::UnsupportedImageType => "ERROR_IMAGE_TYPE_NOT_SUPPORTED",
+            Self::CaptchaIdNotFound => "ERROR_NO_SUCH_CAPCHA_ID",
+            Self::WrongCaptchaId => "ERROR_WRONG_CAPTCHA_ID",
+            Self::IpBlocked => "ERROR_IP_BLOCKED",
+            Self::TaskNotProvided => "ERROR_TASK_ABSENT",
+            Self::TaskNotSupported => "ERROR_TASK_NOT_SUPPORTED",
+            Self::InvalidSiteKey => "ERROR_RECAPTCHA_INVALID_SITEKEY",
+            Self::AccountSuspended => "ERROR_ACCOUNT_SUSPENDED",
+            Self::BadProxy => "ERROR_BAD_PROXY",
+            Self::ProxyConnectionFailed => "ERROR_PROXY_CONNECTION_FAILED",
+            Self::BadParameters => "ERROR_BAD_PARAMETERS",
+            Self::BadImageInstructions => "ERROR_BAD_IMGINSTRUCTIONS",
+            Self::InvalidPageUrl => "ERROR_PAGEURL",
+            Self::EmptyAction => "ERROR_EMPTY_ACTION",
+            Self::TokenExpired => "ERROR_TOKEN_EXPIRED",
+            Self::CaptchaImageBlocked => "ERROR_CAPTCHAIMAGE_BLOCKED",
+            Self::BadTokenOrPageUrl => "ERROR_BAD_TOKEN_OR_PAGEURL",
+            Self::TooManyRequests => "ERROR_TOO_MUCH_REQUESTS",
+            Self::UnsupportedFileExtension => "ERROR_WRONG_FILE_EXTENSION",
+            Self::UploadFailed => "ERROR_UPLOAD",
+            Self::BadProxyFormat => "ERROR_PROXY_FORMAT",
+            Self::InvalidIpAddress => "ERROR_IP_ADDRES",
+            Self::ReportNotRecorded => "ERROR_REPORT_NOT_RECORDED",
+            Self::DuplicateReport => "ERROR_DUPLICATE_REPORT",
+            Self::Unknown { code, .. } => code,
+        }
+    }
+}
+
+impl Error {
+    /// The [`ErrorClass`] this error belongs to
+    #[must_use]
+    pub const fn class(&self) -> ErrorClass {
+        match self {
+            Self::NoSlotAvailable | Self::TooManyRequests => ErrorClass::Throttled,
+            Self::BadProxy | Self::ProxyConnectionFailed | Self::BadProxyFormat => {
+                ErrorClass::Proxy
+            }
+            Self::ImageTooSmall
+            | Self::ImageTooBig
+            | Self::UnsupportedImageType
+            | Self::TaskNotProvided
+            | Self::TaskNotSupported
+            | Self::InvalidSiteKey
+            | Self::BadParameters
+            | Self::BadImageInstructions
+            | Self::InvalidPageUrl
+            | Self::EmptyAction
+            | Self::TokenExpired
+            | Self::CaptchaImageBlocked
+            | Self::BadTokenOrPageUrl
+            | Self::UnsupportedFileExtension
+            | Self::UploadFailed
+            | Self::InvalidIpAddress => ErrorClass::TaskData,
+            Self::UnsolvableCaptcha | Self::BadDuplicates => ErrorClass::Unsolvable,
+            Self::InvalidApiKey
+            | Self::ZeroBalance
+            | Self::IpNotAllowed
+            | Self::IpBlocked
+            | Self::AccountSuspended => ErrorClass::Account,
+            Self::NoSuchMethod
+            | Self::CaptchaIdNotFound
+            | Self::WrongCaptchaId
+            | Self::ReportNotRecorded
+            | Self::DuplicateReport => ErrorClass::Request,
+            Self::Unknown { .. } => ErrorClass::Unknown,
         }
     }
 }
@@ -106,11 +402,15 @@ impl<'a> From<create_task::Response<'a>> for Result<u64> {
         use create_task::Response;
         match val {
             Response::TaskCreated { task_id } => Ok(task_id),
-            Response::Error { error_code, .. } => {
-                let error_code = error_code.as_ref();
-
-                Err(error_code.into())
-            }
+            Response::Error {
+                error_id,
+                error_code,
+                error_description,
+            } => Err(ApiError::new(
+                &error_code,
+                error_id,
+                error_description.as_deref(),
+            )),
         }
     }
 }
@@ -124,21 +424,33 @@ where
         match val {
             Response::Ready(solution) => Ok(Some(solution)),
             Response::Processing => Ok(None),
-            Response::Error { error_code, .. } => {
-                let error_code = error_code.as_ref();
-
-                Err(error_code.into())
-            }
+            Response::Error {
+                error_id,
+                error_code,
+                error_description,
+            } => Err(ApiError::new(
+                &error_code,
+                error_id,
+                error_description.as_deref(),
+            )),
         }
     }
 }
 
-impl<'a> From<get_balance::Response<'a>> for Result<f64> {
+impl From<get_balance::Response<'_>> for Result<Money> {
     fn from(value: get_balance::Response) -> Self {
         use get_balance::Response;
         match value {
             Response::Success { balance } => Ok(balance),
-            Response::Error { error_code } => Err(error_code.as_ref().into()),
+            Response::Error {
+                error_id,
+                error_code,
+                error_description,
+            } => Err(ApiError::new(
+                &error_code,
+                error_id,
+                error_description.as_deref(),
+            )),
         }
     }
 }
@@ -148,7 +460,128 @@ impl<'a> From<report::Response<'a>> for Result<()> {
         use report::Response;
         match value {
             Response::Success => Ok(()),
-            Response::Error { error_code } => Err(error_code.as_ref().into()),
+            Response::Error {
+                error_id,
+                error_code,
+                error_description,
+            } => Err(ApiError::new(
+                &error_code,
+                error_id,
+                error_description.as_deref(),
+            )),
+        }
+    }
+}
+
+impl<'a> From<pingback_addresses::Response<'a>> for Result<pingback_addresses::Payload<'a>> {
+    fn from(value: pingback_addresses::Response<'a>) -> Self {
+        use pingback_addresses::{Payload, Status};
+        match value {
+            pingback_addresses::Response {
+                status: Status::Success,
+                request,
+                ..
+            } => Ok(request),
+            pingback_addresses::Response {
+                request: Payload::Text(error_code),
+                error_text,
+                ..
+            } => Err(ApiError::from_code(&error_code, error_text.as_deref())),
+            pingback_addresses::Response { error_text, .. } => {
+                Err(ApiError::from_code(UNKNOWN_CODE, error_text.as_deref()))
+            }
         }
     }
 }
+
+impl<'a> From<submit::Response<'a>> for Result<u64> {
+    fn from(value: submit::Response<'a>) -> Self {
+        match value {
+            submit::Response {
+                status: Status::Success,
+                request,
+                ..
+            } => request
+                .parse()
+                .map_err(|_| ApiError::from_code(&request, None)),
+            submit::Response {
+                request,
+                error_text,
+                ..
+            } => Err(ApiError::from_code(&request, error_text.as_deref())),
+        }
+    }
+}
+
+/// Returns `Ok(None)` while the task is still being processed
+impl<'a> From<res::Response<'a>> for Result<Option<res::Response<'a>>> {
+    fn from(value: res::Response<'a>) -> Self {
+        match value {
+            res::Response {
+                status: Status::Success,
+                ..
+            } => Ok(Some(value)),
+            res::Response { request, .. } if request == res::NOT_READY => Ok(None),
+            res::Response {
+                request,
+                error_text,
+                ..
+            } => Err(ApiError::from_code(
+                request.as_str().unwrap_or(UNKNOWN_CODE),
+                error_text.as_deref(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ApiError, Error, ErrorClass, UNKNOWN_CODE};
+
+    #[test]
+    fn unknown_error_code() {
+        let error = ApiError::new("ERROR_SOMETHING_NEW", 999, Some("Something new happened"));
+
+        assert!(matches!(
+            error.kind,
+            Error::Unknown {
+                ref code,
+                description: Some(ref description),
+                error_id: Some(999),
+            } if &**code == "ERROR_SOMETHING_NEW" && &**description == "Something new happened"
+        ));
+        assert_eq!(error.code(), "ERROR_SOMETHING_NEW");
+    }
+
+    #[test]
+    fn missing_error_code() {
+        let error = ApiError::from_code("", Some("Something went wrong"));
+
+        assert_eq!(error.code(), UNKNOWN_CODE);
+        assert_eq!(error.class(), ErrorClass::Unknown);
+    }
+
+    #[test]
+    fn known_error_code() {
+        let error = ApiError::new("ERROR_WRONG_CAPTCHA_ID", 16, Some("Wrong captcha id"));
+
+        assert!(matches!(error.kind, Error::WrongCaptchaId));
+        assert_eq!(error.error_id, Some(16));
+        assert_eq!(error.description.as_deref(), Some("Wrong captcha id"));
+    }
+
+    #[test]
+    fn classification() {
+        assert_eq!(Error::NoSlotAvailable.class(), ErrorClass::Throttled);
+        assert_eq!(Error::BadProxy.class(), ErrorClass::Proxy);
+        assert_eq!(Error::InvalidSiteKey.class(), ErrorClass::TaskData);
+        assert_eq!(Error::ZeroBalance.class(), ErrorClass::Account);
+        assert_eq!(
+            ApiError::new("ERROR_SOMETHING_NEW", 999, None).class(),
+            ErrorClass::Unknown
+        );
+
+        assert!(ErrorClass::Throttled.is_retryable());
+        assert!(!ErrorClass::Account.is_retryable());
+    }
+}
@@ -0,0 +1,27 @@
+use std::sync::Arc;
+
+use crate::Money;
+
+use super::{BudgetPeriod, TaskId};
+
+/// Something that happened to a [`crate::CaptchaSolver`] that is worth
+/// knowing about, e.g. for accounting. Set a handler for these with
+/// [`crate::captcha_solver::CaptchaSolverBuilder::on_event`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Event {
+    /// A task was cancelled while it was being solved. 2captcha may still
+    /// solve it and charge for it, so its id can be used to get or report
+    /// its result later
+    TaskAbandoned { task_id: TaskId },
+
+    /// The spend of a period reached the soft limit of the solver's
+    /// [`super::Budget`]. It is emitted once per period
+    SoftLimitReached {
+        period: BudgetPeriod,
+        spent: Money,
+        limit: Money,
+    },
+}
+
+pub type EventHandler = Arc<dyn Fn(&Event) + Send + Sync>;
@@ -0,0 +1,579 @@
+#![allow(clippy::module_name_repetitions)]
+
+use std::{
+    sync::{Mutex, PoisonError},
+    time::Duration,
+};
+
+use tokio::time::Instant;
+
+use crate::{
+    captcha::{
+        solution::{Solution, Status},
+        Captcha,
+    },
+    CaptchaSolver, Error, Result,
+};
+
+use super::{budget::Job, error::ErrorClass, SolveOptions};
+
+/// How long a backend is skipped for after failing by default
+const DEFAULT_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// Solves tasks with the first of several [`CaptchaSolver`]s that is able
+/// to, e.g. solvers with different API keys or [`super::Provider`]s.
+///
+/// When a backend fails to create the task with an error that says nothing
+/// about the task itself, i.e. an [`ErrorClass::Account`] error such as
+/// `ZeroBalance` or `IpBlocked`, an [`ErrorClass::Throttled`] error such as
+/// `NoSlotAvailable`, or an HTTP error the request certainly did not get
+/// through with, the task is sent to the next backend and the failed one is
+/// skipped until its cooldown is over. Any other error is returned right
+/// away, as another backend would fail the same way. So is any error once
+/// a task was created, as it may still be solved and charged for.
+///
+/// # Example
+/// ```no_run
+/// use std::time::Duration;
+/// use captcha_oxide::{
+///     CaptchaSolver,
+///     captcha::types::normal_captcha::NormalCaptcha,
+///     captcha_solver::{FailoverSolver, Provider, Selection},
+///     Captcha,
+/// };
+///
+/// async fn example() -> captcha_oxide::Result<()> {
+///     let solver = FailoverSolver::builder()
+///         .backend("main", CaptchaSolver::new("MAIN API KEY"))
+///         .backend(
+///             "rucaptcha",
+///             CaptchaSolver::builder()
+///                 .api_key("RUCAPTCHA API KEY")
+///                 .provider(Provider::rucaptcha())
+///                 .build(),
+///         )
+///         .selection(Selection::Ordered)
+///         .cooldown(Duration::from_secs(300))
+///         .build();
+///
+///     let captcha = NormalCaptcha::builder().body("R0lGODlhAQABAAAAACw=").build();
+///     let solved = solver.solve(&captcha).await?;
+///
+///     println!("{} solved it", solver.health()[solved.backend].name);
+///     Ok(())
+/// }
+/// ```
+pub struct FailoverSolver {
+    backends: Box<[Backend]>,
+    selection: Selection,
+    cooldown: Duration,
+    state: Mutex<Box<[State]>>,
+}
+
+struct Backend {
+    name: Box<str>,
+    solver: CaptchaSolver,
+    weight: u32,
+}
+
+#[derive(Default)]
+struct State {
+    unhealthy_until: Option<Instant>,
+    last_error: Option<Box<str>>,
+    successes: u64,
+    failures: u64,
+
+    /// The running weight of smooth weighted round robin selection
+    current_weight: i64,
+}
+
+/// The order in which the backends of a [`FailoverSolver`] are tried
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Selection {
+    /// In the order they were added, so the first backend gets every task
+    /// while it is healthy
+    #[default]
+    Ordered,
+
+    /// Spread among the healthy backends in proportion to their weight,
+    /// trying the others in order of weight if the chosen one fails
+    Weighted,
+}
+
+/// The health of a backend, as reported by [`FailoverSolver::health`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct BackendHealth {
+    /// The name the backend was added with
+    pub name: Box<str>,
+
+    /// Whether tasks are being sent to the backend
+    pub healthy: bool,
+
+    /// How long until the backend is tried again, if it is not healthy
+    pub retry_in: Option<Duration>,
+
+    /// The error code, or the message for errors without a code, of the
+    /// last error the backend failed with
+    pub last_error: Option<Box<str>>,
+
+    /// How many tasks the backend solved
+    pub successes: u64,
+
+    /// How many tasks the backend failed to solve
+    pub failures: u64,
+}
+
+/// A solution along with the index of the backend that solved it, which is
+/// needed to report it
+#[derive(Debug)]
+#[must_use]
+pub struct FailoverSolution<'a, T>
+where
+    T: Captcha,
+{
+    /// The index of the backend in the order they were added
+    pub backend: usize,
+
+    pub solution: Solution<'a, T>,
+}
+
+impl FailoverSolver {
+    /// Returns a new instance of [`FailoverSolverBuilder`]
+    #[must_use]
+    pub const fn builder() -> FailoverSolverBuilder {
+        FailoverSolverBuilder::new()
+    }
+
+    /// Solves the given puzzle with the first backend able to
+    ///
+    /// # Errors
+    /// Returns the error of the last backend that was tried if every
+    /// backend failed, [`crate::Error::NoBackendAvailable`] if every
+    /// backend is cooling down, and any error that is not caused by the
+    /// backend itself as soon as it happens
+    pub async fn solve<'a, T>(&self, task: &T) -> Result<FailoverSolution<'a, T>>
+    where
+        T: Captcha,
+    {
+        self.solve_with(task, SolveOptions::new()).await
+    }
+
+    /// Same as [`FailoverSolver::solve`], overriding the [`SolveOptions`] of
+    /// every backend with the ones that are set in `options`. The solution
+    /// is always waited for, even if a backend has a `callback_url`
+    ///
+    /// # Errors
+    /// See [`FailoverSolver::solve`]
+    pub async fn solve_with<'a, T>(
+        &self,
+        task: &T,
+        options: SolveOptions,
+    ) -> Result<FailoverSolution<'a, T>>
+    where
+        T: Captcha,
+    {
+        let mut last_error = None;
+
+        for index in self.order() {
+            let solver = &self.backends[index].solver;
+            let job = Job::default();
+            let result = solver
+                .solve_until(task, options.or(solver.solve_options), None, &job)
+                .await;
+
+            match result {
+                Ok(solution) => {
+                    self.record(index, None);
+                    return Ok(FailoverSolution {
+                        backend: index,
+                        solution,
+                    });
+                }
+                Err(e) => {
+                    let fail_over = !job.has_task() && fails_over(&e);
+                    self.record(index, Some((&e, fail_over)));
+
+                    if !fail_over {
+                        return Err(e);
+                    }
+
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(last_error.unwrap_or(Error::NoBackendAvailable))
+    }
+
+    /// Reports the solution to the backend that solved it
+    ///
+    /// # Errors
+    /// This function can error if the HTTP request is not sent successfully,
+    /// if the response cannot be parsed or if the API returns an error
+    pub async fn report<T>(&self, solved: FailoverSolution<'_, T>, status: Status) -> Result<()>
+    where
+        T: Captcha,
+    {
+        self.backends[solved.backend]
+            .solver
+            .report(solved.solution, status)
+            .await
+    }
+
+    /// The solver of the backend at `index`, in the order they were added
+    #[must_use]
+    pub fn backend(&self, index: usize) -> Option<&CaptchaSolver> {
+        self.backends.get(index).map(|backend| &backend.solver)
+    }
+
+    /// The health of every backend, in the order they were added
+    #[must_use]
+    pub fn health(&self) -> Vec<BackendHealth> {
+        let now = Instant::now();
+        let state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+
+        self.backends
+            .iter()
+            .zip(state.iter())
+            .map(|(backend, state)| {
+                let retry_in = state
+                    .unhealthy_until
+                    .filter(|&until| until > now)
+                    .map(|until| until - now);
+
+                BackendHealth {
+                    name: backend.name.clone(),
+                    healthy: retry_in.is_none(),
+                    retry_in,
+                    last_error: state.last_error.clone(),
+                    successes: state.successes,
+                    failures: state.failures,
+                }
+            })
+            .collect()
+    }
+
+    /// The indices of the healthy backends, in the order they should be tried
+    fn order(&self) -> Vec<usize> {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let mut healthy: Vec<_> = (0..self.backends.len())
+            .filter(|&index| {
+                state[index]
+                    .unhealthy_until
+                    .map_or(true, |until| until <= now)
+            })
+            .collect();
+
+        if self.selection == Selection::Ordered || healthy.is_empty() {
+            return healthy;
+        }
+
+        let total: i64 = healthy
+            .iter()
+            .map(|&index| i64::from(self.backends[index].weight))
+            .sum();
+
+        for &index in &healthy {
+            state[index].current_weight += i64::from(self.backends[index].weight);
+        }
+
+        let chosen = healthy
+            .iter()
+            .copied()
+            .max_by_key(|&index| (state[index].current_weight, std::cmp::Reverse(index)))
+            .unwrap_or_default();
+        state[chosen].current_weight -= total;
+        drop(state);
+
+        healthy.sort_by_key(|&index| {
+            (
+                index != chosen,
+                std::cmp::Reverse(self.backends[index].weight),
+            )
+        });
+        healthy
+    }
+
+    /// Records the outcome of a solve, starting the cooldown of the backend
+    /// if it failed in a way that makes it fail over
+    fn record(&self, index: usize, error: Option<(&Error, bool)>) {
+        let failure = error.map(|(error, fail_over)| {
+            let code: Box<str> = match error {
+                Error::TwoCaptchaError(e) => e.code().into(),
+                e => e.to_string().into(),
+            };
+            let until = Instant::now().checked_add(self.cooldown);
+
+            (code, until.filter(|_| fail_over))
+        });
+
+        let mut states = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let state = &mut states[index];
+
+        if let Some((code, until)) = failure {
+            state.failures += 1;
+            state.last_error = Some(code);
+            state.unhealthy_until = until.or(state.unhealthy_until);
+        } else {
+            state.successes += 1;
+            state.unhealthy_until = None;
+        }
+        drop(states);
+    }
+}
+
+/// Whether creating a task failed with `error` because of the backend
+/// rather than the task, and certainly without creating it, so another
+/// backend may succeed
+const fn fails_over(error: &Error) -> bool {
+    error.is_rejection()
+        && (matches!(error, Error::Http(_))
+            || matches!(error.class(), ErrorClass::Account | ErrorClass::Throttled))
+}
+
+pub struct FailoverSolverBuilder {
+    backends: Vec<Backend>,
+    selection: Selection,
+    cooldown: Duration,
+}
+
+impl FailoverSolverBuilder {
+    #[must_use = "A builder type must have its `build` method called to build the target type"]
+    pub const fn new() -> Self {
+        Self {
+            backends: Vec::new(),
+            selection: Selection::Ordered,
+            cooldown: DEFAULT_COOLDOWN,
+        }
+    }
+
+    /// Adds a backend with a weight of 1
+    #[must_use = "A builder type must have its `build` method called to build the target type"]
+    pub fn backend<T>(self, name: T, solver: CaptchaSolver) -> Self
+    where
+        T: Into<Box<str>>,
+    {
+        self.weighted_backend(name, solver, 1)
+    }
+
+    /// Adds a backend that gets `weight` shares of the tasks with
+    /// [`Selection::Weighted`]. Backends with a weight of 0 are only tried
+    /// when the others fail
+    #[must_use = "A builder type must have its `build` method called to build the target type"]
+    pub fn weighted_backend<T>(mut self, name: T, solver: CaptchaSolver, weight: u32) -> Self
+    where
+        T: Into<Box<str>>,
+    {
+        self.backends.push(Backend {
+            name: name.into(),
+            solver,
+            weight,
+        });
+        self
+    }
+
+    /// Sets the order in which backends are tried. Defaults to
+    /// [`Selection::Ordered`]
+    #[must_use = "A builder type must have its `build` method called to build the target type"]
+    pub const fn selection(mut self, selection: Selection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// Sets how long a backend is skipped for after it fails. Defaults to
+    /// 60 seconds
+    #[must_use = "A builder type must have its `build` method called to build the target type"]
+    pub const fn cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> FailoverSolver {
+        FailoverSolver {
+            state: Mutex::new(self.backends.iter().map(|_| State::default()).collect()),
+            backends: self.backends.into(),
+            selection: self.selection,
+            cooldown: self.cooldown,
+        }
+    }
+}
+
+impl Default for FailoverSolverBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use captcha_oxide_testkit::{Fault, MockServer, TaskScript};
+    use serde_json::json;
+
+    use super::{FailoverSolver, Selection};
+    use crate::{
+        captcha_solver::{error::ErrorClass, RetryPolicy},
+        mock, CaptchaSolver, Error,
+    };
+
+    fn ready() -> TaskScript {
+        TaskScript::new().ready(json!({ "text": "w68hp" }))
+    }
+
+    fn backend(server: &MockServer) -> CaptchaSolver {
+        mock::builder(server)
+            .retry_policy(RetryPolicy::none())
+            .build()
+    }
+
+    #[tokio::test]
+    async fn fails_over_to_the_next_backend() {
+        let (first, second) = (MockServer::start().await, MockServer::start().await);
+        first.inject("createTask", Fault::ErrorCode("ERROR_ZERO_BALANCE"), 1);
+        second.enqueue(ready());
+        second.enqueue(ready());
+
+        let solver = FailoverSolver::builder()
+            .backend("first", backend(&first))
+            .backend("second", backend(&second))
+            .build();
+
+        let result = solver.solve(&mock::captcha()).await.unwrap();
+
+        assert_eq!(result.backend, 1);
+        assert_eq!(result.solution.solution.text, "w68hp");
+
+        let health = solver.health();
+        assert!(!health[0].healthy);
+        assert_eq!(health[0].last_error.as_deref(), Some("ERROR_ZERO_BALANCE"));
+        assert!(health[0].retry_in.is_some());
+        assert!(health[1].healthy);
+        assert_eq!(health[1].successes, 1);
+
+        // The first backend is skipped while it is cooling down
+        assert_eq!(solver.solve(&mock::captcha()).await.unwrap().backend, 1);
+        assert_eq!(first.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn recovers_after_the_cooldown() {
+        let (first, second) = (MockServer::start().await, MockServer::start().await);
+        first.inject("createTask", Fault::ErrorCode("ERROR_NO_SLOT_AVAILABLE"), 1);
+        first.enqueue(ready());
+        second.enqueue(ready());
+
+        let solver = FailoverSolver::builder()
+            .backend("first", backend(&first))
+            .backend("second", backend(&second))
+            .cooldown(Duration::from_millis(50))
+            .build();
+
+        assert_eq!(solver.solve(&mock::captcha()).await.unwrap().backend, 1);
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+
+        assert_eq!(solver.solve(&mock::captcha()).await.unwrap().backend, 0);
+        assert!(solver.health()[0].healthy);
+    }
+
+    #[tokio::test]
+    async fn created_tasks_do_not_fail_over() {
+        let (first, second) = (MockServer::start().await, MockServer::start().await);
+        first.enqueue(
+            TaskScript::new()
+                .processing(1)
+                .ready(json!({ "text": "w68hp" })),
+        );
+        first.inject("getTaskResult", Fault::Status(503), 1);
+
+        let solver = FailoverSolver::builder()
+            .backend("first", backend(&first))
+            .backend("second", backend(&second))
+            .build();
+
+        let error = solver.solve(&mock::captcha()).await.unwrap_err();
+
+        assert!(matches!(error, Error::Http(_)));
+        assert!(second.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn ambiguous_submit_failures_do_not_fail_over() {
+        let (first, second) = (MockServer::start().await, MockServer::start().await);
+        first.enqueue(ready());
+        first.inject("createTask", Fault::Drop, 1);
+
+        let solver = FailoverSolver::builder()
+            .backend("first", backend(&first))
+            .backend("second", backend(&second))
+            .build();
+
+        assert!(solver.solve(&mock::captcha()).await.is_err());
+        assert!(second.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn task_errors_do_not_fail_over() {
+        let (first, second) = (MockServer::start().await, MockServer::start().await);
+        first.inject(
+            "createTask",
+            Fault::ErrorCode("ERROR_ZERO_CAPTCHA_FILESIZE"),
+            1,
+        );
+
+        let solver = FailoverSolver::builder()
+            .backend("first", backend(&first))
+            .backend("second", backend(&second))
+            .build();
+
+        let error = solver.solve(&mock::captcha()).await.unwrap_err();
+
+        assert_eq!(error.class(), ErrorClass::TaskData);
+        assert!(second.requests().is_empty());
+        assert!(solver.health()[0].healthy);
+    }
+
+    #[tokio::test]
+    async fn every_backend_cooling_down() {
+        let server = MockServer::start().await;
+        server.inject("createTask", Fault::ErrorCode("ERROR_IP_BLOCKED"), 1);
+
+        let solver = FailoverSolver::builder()
+            .backend("only", backend(&server))
+            .build();
+
+        let error = solver.solve(&mock::captcha()).await.unwrap_err();
+        assert_eq!(error.class(), ErrorClass::Account);
+
+        let error = solver.solve(&mock::captcha()).await.unwrap_err();
+        assert!(matches!(error, Error::NoBackendAvailable));
+    }
+
+    #[tokio::test]
+    async fn weighted_selection() {
+        let (first, second) = (MockServer::start().await, MockServer::start().await);
+        for _ in 0..6 {
+            first.enqueue(ready());
+            second.enqueue(ready());
+        }
+
+        let solver = FailoverSolver::builder()
+            .weighted_backend("first", backend(&first), 2)
+            .weighted_backend("second", backend(&second), 1)
+            .selection(Selection::Weighted)
+            .build();
+
+        let mut backends = Vec::new();
+        for _ in 0..6 {
+            backends.push(solver.solve(&mock::captcha()).await.unwrap().backend);
+        }
+
+        assert_eq!(backends.iter().filter(|&&backend| backend == 0).count(), 4);
+        assert_eq!(backends.iter().filter(|&&backend| backend == 1).count(), 2);
+    }
+}
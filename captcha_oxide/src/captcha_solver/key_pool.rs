@@ -0,0 +1,497 @@
+#![allow(clippy::module_name_repetitions)]
+
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt::{self, Debug},
+    sync::{Mutex, PoisonError},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use tokio::{sync::Notify, time::Instant};
+
+use crate::{Error, Money, Result};
+
+use super::TaskId;
+
+/// How long a task is remembered once its cost was charged, so fetching its
+/// result again does not charge it twice
+const CHARGE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// An API key along with the limits on its use, for solvers that share
+/// several keys. The key itself is left out of the [`Debug`] output.
+///
+/// # Example
+/// ```
+/// use captcha_oxide::{CaptchaSolver, Money, captcha_solver::KeyConfig};
+///
+/// let solver = CaptchaSolver::builder()
+///     .api_key(KeyConfig::new("FIRST API KEY").max_concurrent(10))
+///     .add_api_key(KeyConfig::new("SECOND API KEY").daily_spend_limit(Money::from(5)))
+///     .build();
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct KeyConfig {
+    key: Box<str>,
+    max_concurrent: Option<usize>,
+    daily_spend_limit: Option<Money>,
+}
+
+impl KeyConfig {
+    /// An API key with no limits
+    #[must_use]
+    pub fn new<T>(key: T) -> Self
+    where
+        T: Into<Box<str>>,
+    {
+        Self {
+            key: key.into(),
+            max_concurrent: None,
+            daily_spend_limit: None,
+        }
+    }
+
+    /// Caps the tasks created with this key that are being waited on at
+    /// once. A task counts towards the cap until its [`super::PendingTask`]
+    /// is dropped, and new tasks wait for a slot when every key is at its
+    /// cap. A cap of 0 is treated as 1
+    #[must_use]
+    pub const fn max_concurrent(mut self, max_concurrent: usize) -> Self {
+        self.max_concurrent = Some(if max_concurrent == 0 {
+            1
+        } else {
+            max_concurrent
+        });
+        self
+    }
+
+    /// Stops creating tasks with this key once the cost of the tasks solved
+    /// with it since midnight UTC reaches `limit`
+    #[must_use]
+    pub const fn daily_spend_limit(mut self, limit: Money) -> Self {
+        self.daily_spend_limit = Some(limit);
+        self
+    }
+}
+
+impl Debug for KeyConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyConfig")
+            .field("key", &"[redacted]")
+            .field("max_concurrent", &self.max_concurrent)
+            .field("daily_spend_limit", &self.daily_spend_limit)
+            .finish()
+    }
+}
+
+impl From<&str> for KeyConfig {
+    fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<String> for KeyConfig {
+    fn from(value: String) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<Box<str>> for KeyConfig {
+    fn from(value: Box<str>) -> Self {
+        Self::new(value)
+    }
+}
+
+/// The API keys of a [`crate::CaptchaSolver`], which are rotated through
+/// as tasks are created
+pub struct KeyPool {
+    keys: Box<[KeyConfig]>,
+    state: Mutex<State>,
+    released: Notify,
+}
+
+struct State {
+    next: usize,
+    in_flight: Vec<usize>,
+    spent: Vec<Money>,
+
+    /// The day, counted from the Unix epoch, `spent` is for
+    day: u64,
+
+    /// The ids of the tasks that were charged in the last [`CHARGE_TTL`]
+    charged: HashSet<u64>,
+    charged_at: VecDeque<(Instant, u64)>,
+}
+
+/// A key taken from the pool to create a task with, which counts towards
+/// the key's concurrency cap until it is dropped
+pub struct Lease<'a> {
+    pool: &'a KeyPool,
+    index: usize,
+}
+
+impl KeyPool {
+    /// # Panics
+    /// If `keys` is empty
+    pub fn new(keys: Vec<KeyConfig>) -> Self {
+        assert!(!keys.is_empty(), "A key pool needs at least one key");
+
+        Self {
+            state: Mutex::new(State {
+                next: 0,
+                in_flight: vec![0; keys.len()],
+                spent: vec![Money::ZERO; keys.len()],
+                day: today(),
+                charged: HashSet::new(),
+                charged_at: VecDeque::new(),
+            }),
+            keys: keys.into(),
+            released: Notify::new(),
+        }
+    }
+
+    /// The first key, used for requests that are not about a task, such as
+    /// getting the balance
+    pub fn primary(&self) -> &str {
+        &self.keys[0].key
+    }
+
+    /// The key `task_id` was created with, or the primary key if the id
+    /// does not say
+    ///
+    /// # Errors
+    /// Returns [`Error::UnknownApiKey`] if the pool has no key with the
+    /// fingerprint of the id
+    pub fn key_for(&self, task_id: TaskId) -> Result<&str> {
+        self.index_of(task_id).map(|index| &*self.keys[index].key)
+    }
+
+    fn index_of(&self, task_id: TaskId) -> Result<usize> {
+        let Some(fingerprint) = task_id.key() else {
+            return Ok(0);
+        };
+
+        self.keys
+            .iter()
+            .position(|key| self::fingerprint(&key.key) == fingerprint)
+            .ok_or(Error::UnknownApiKey { task_id })
+    }
+
+    /// Takes the next key with room for another task, waiting for a task to
+    /// finish if every key is at its concurrency cap
+    ///
+    /// # Errors
+    /// Returns [`Error::SpendLimitReached`] if every key has reached its
+    /// daily spend limit
+    pub async fn acquire(&self) -> Result<Lease<'_>> {
+        loop {
+            let released = self.released.notified();
+
+            if let Some(index) = self.try_acquire()? {
+                return Ok(Lease { pool: self, index });
+            }
+
+            released.await;
+        }
+    }
+
+    /// Takes the next key with room for another task, or returns `Ok(None)`
+    /// if every key that is under its spend limit is at its concurrency cap
+    fn try_acquire(&self) -> Result<Option<usize>> {
+        let mut state = self.lock();
+        state.roll_over(today());
+
+        let available = (0..self.keys.len())
+            .map(|offset| (state.next + offset) % self.keys.len())
+            .find(|&index| {
+                let max_concurrent = self.keys[index].max_concurrent;
+
+                self.under_spend_limit(&state, index)
+                    && max_concurrent.map_or(true, |max| state.in_flight[index] < max)
+            });
+
+        let result = match available {
+            Some(index) => {
+                state.in_flight[index] += 1;
+                state.next = (index + 1) % self.keys.len();
+                Ok(Some(index))
+            }
+            None if (0..self.keys.len()).any(|index| self.under_spend_limit(&state, index)) => {
+                Ok(None)
+            }
+            None => Err(Error::SpendLimitReached),
+        };
+        drop(state);
+
+        result
+    }
+
+    fn under_spend_limit(&self, state: &State, index: usize) -> bool {
+        self.keys[index]
+            .daily_spend_limit
+            .map_or(true, |limit| state.spent[index] < limit)
+    }
+
+    /// Adds the cost of the solved task to the spend of the key it was
+    /// created with, whether it was created through this pool or resumed.
+    /// Returns whether the task was charged, which only happens the first
+    /// time its cost is known
+    pub fn charge(&self, task_id: TaskId, cost: Money) -> bool {
+        let Ok(index) = self.index_of(task_id) else {
+            return false;
+        };
+
+        let id = u64::from(task_id);
+        let now = Instant::now();
+        let mut state = self.lock();
+        state.roll_over(today());
+
+        while let Some(&(charged_at, expired)) = state.charged_at.front() {
+            if now.duration_since(charged_at) < CHARGE_TTL {
+                break;
+            }
+
+            state.charged_at.pop_front();
+            state.charged.remove(&expired);
+        }
+
+        if !state.charged.insert(id) {
+            return false;
+        }
+
+        state.charged_at.push_back((now, id));
+        state.spent[index] += cost;
+        true
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl State {
+    /// Forgets the spend of previous days
+    fn roll_over(&mut self, today: u64) {
+        if self.day != today {
+            self.day = today;
+            self.spent.iter_mut().for_each(|spent| *spent = Money::ZERO);
+        }
+    }
+}
+
+impl Lease<'_> {
+    /// The API key to create the task with
+    pub fn key(&self) -> &str {
+        &self.pool.keys[self.index].key
+    }
+
+    /// Returns `task_id` along with the fingerprint of the key of this
+    /// lease, so the task is polled and reported with it, and its cost is
+    /// charged to the key once it is solved
+    pub fn pin(&self, task_id: TaskId) -> TaskId {
+        task_id.with_key(fingerprint(self.key()))
+    }
+}
+
+impl Drop for Lease<'_> {
+    fn drop(&mut self) {
+        self.pool.lock().in_flight[self.index] -= 1;
+        self.pool.released.notify_waiters();
+    }
+}
+
+/// Identifies an API key in the [`TaskId`]s created with it without giving
+/// the key away. This is FNV-1a, as the hashers of the standard library
+/// may change between releases and stored ids must keep matching
+fn fingerprint(key: &str) -> u64 {
+    key.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// The current day, counted from the Unix epoch in UTC
+fn today() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs() / SECONDS_PER_DAY)
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use captcha_oxide_testkit::{MockServer, TaskScript};
+    use serde_json::json;
+
+    use super::KeyConfig;
+    use crate::{
+        captcha::{solution::Status, types::normal_captcha::NormalCaptcha},
+        mock, Error, Money,
+    };
+
+    fn ready() -> TaskScript {
+        TaskScript::new().ready(json!({ "text": "w68hp" }))
+    }
+
+    /// The keys the requests to `path` were sent with
+    fn keys(server: &MockServer, path: &str) -> Vec<String> {
+        server
+            .requests()
+            .into_iter()
+            .filter(|request| request.path == path)
+            .map(|request| request.body["clientKey"].as_str().unwrap().to_owned())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn rotates_keys_and_pins_tasks() {
+        let server = MockServer::start().await;
+        server.enqueue(ready());
+        server.enqueue(ready());
+
+        let solver = mock::builder(&server)
+            .api_key("FIRST_KEY")
+            .add_api_key("SECOND_KEY")
+            .build();
+
+        let first = mock::solved(solver.solve(&mock::captcha()).await).unwrap();
+        let second = mock::solved(solver.solve(&mock::captcha()).await).unwrap();
+        solver.report(second, Status::Correct).await.unwrap();
+        solver.report(first, Status::Incorrect).await.unwrap();
+
+        assert_eq!(keys(&server, "/createTask"), ["FIRST_KEY", "SECOND_KEY"]);
+        assert_eq!(keys(&server, "/getTaskResult"), ["FIRST_KEY", "SECOND_KEY"]);
+        assert_eq!(keys(&server, "/reportCorrect"), ["SECOND_KEY"]);
+        assert_eq!(keys(&server, "/reportIncorrect"), ["FIRST_KEY"]);
+    }
+
+    #[tokio::test]
+    async fn resumes_with_the_key_of_the_task() {
+        let server = MockServer::start().await;
+        server.enqueue(ready());
+        server.enqueue(ready());
+
+        let solver = || {
+            mock::builder(&server)
+                .api_key("FIRST_KEY")
+                .add_api_key("SECOND_KEY")
+                .build()
+        };
+
+        let stored = {
+            let solver = solver();
+            let _first = solver.submit(&mock::captcha()).await.unwrap();
+            let second = solver.submit(&mock::captcha()).await.unwrap();
+
+            serde_json::to_string(&second.into_id()).unwrap()
+        };
+
+        // The keys of the solver that resumes the task are in another order
+        let solver = mock::builder(&server)
+            .api_key("SECOND_KEY")
+            .add_api_key("FIRST_KEY")
+            .build();
+        let task_id = serde_json::from_str(&stored).unwrap();
+        let solution = solver
+            .resume::<NormalCaptcha>(task_id)
+            .wait()
+            .await
+            .unwrap();
+        solver.report(solution, Status::Correct).await.unwrap();
+
+        assert_eq!(keys(&server, "/createTask"), ["FIRST_KEY", "SECOND_KEY"]);
+        assert_eq!(keys(&server, "/getTaskResult"), ["SECOND_KEY"]);
+        assert_eq!(keys(&server, "/reportCorrect"), ["SECOND_KEY"]);
+    }
+
+    #[tokio::test]
+    async fn unknown_keys() {
+        let server = MockServer::start().await;
+        server.enqueue(ready());
+
+        let stored = {
+            let solver = mock::builder(&server).api_key("OLD_KEY").build();
+            serde_json::to_string(&solver.submit(&mock::captcha()).await.unwrap().into_id())
+                .unwrap()
+        };
+
+        let solver = mock::builder(&server).api_key("NEW_KEY").build();
+        let task_id = serde_json::from_str(&stored).unwrap();
+        let error = solver
+            .resume::<NormalCaptcha>(task_id)
+            .wait()
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, Error::UnknownApiKey { .. }));
+        assert_eq!(keys(&server, "/getTaskResult"), Vec::<String>::new());
+    }
+
+    #[tokio::test]
+    async fn charges_resumed_tasks() {
+        let server = MockServer::start().await;
+        server.enqueue(ready().cost("0.002"));
+        server.enqueue(ready());
+
+        let solver = || {
+            mock::builder(&server)
+                .api_key(KeyConfig::new("API_KEY").daily_spend_limit(Money::new(2, 3)))
+                .build()
+        };
+
+        let stored =
+            serde_json::to_string(&solver().submit(&mock::captcha()).await.unwrap().into_id())
+                .unwrap();
+
+        let solver = solver();
+        let task_id = serde_json::from_str(&stored).unwrap();
+        let _ = solver
+            .resume::<NormalCaptcha>(task_id)
+            .wait()
+            .await
+            .unwrap();
+        let error = solver.solve(&mock::captcha()).await.unwrap_err();
+
+        assert!(matches!(error, Error::SpendLimitReached));
+    }
+
+    #[tokio::test]
+    async fn waits_for_a_slot() {
+        let server = MockServer::start().await;
+        server.enqueue(ready());
+        server.enqueue(ready());
+
+        let solver = mock::builder(&server)
+            .api_key(KeyConfig::new("API_KEY").max_concurrent(1))
+            .build();
+
+        let captcha = mock::captcha();
+        let pending = solver.submit(&captcha).await.unwrap();
+        let second = tokio::time::timeout(Duration::from_millis(50), solver.submit(&captcha));
+        assert!(second.await.is_err());
+
+        drop(pending);
+        assert!(solver.submit(&captcha).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn stops_at_the_spend_limit() {
+        let server = MockServer::start().await;
+        server.enqueue(ready().cost("0.002"));
+        server.enqueue(ready().cost("0.002"));
+
+        let solver = mock::builder(&server)
+            .api_key(KeyConfig::new("FIRST_KEY").daily_spend_limit(Money::new(2, 3)))
+            .add_api_key(KeyConfig::new("SECOND_KEY").daily_spend_limit(Money::new(1, 3)))
+            .build();
+
+        for _ in 0..2 {
+            let _ = mock::solved(solver.solve(&mock::captcha()).await).unwrap();
+        }
+        let error = solver.solve(&mock::captcha()).await.unwrap_err();
+
+        assert!(matches!(error, Error::SpendLimitReached));
+        assert_eq!(keys(&server, "/createTask"), ["FIRST_KEY", "SECOND_KEY"]);
+    }
+}
@@ -0,0 +1,122 @@
+use std::{
+    sync::{Mutex, PoisonError},
+    time::Duration,
+};
+
+use tokio::time::Instant;
+
+use crate::Error;
+
+/// How a single solve went, as seen from the client. Every
+/// [`crate::captcha::solution::Solution`] returned by a
+/// [`crate::CaptchaSolver`] carries one
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SolveMetrics {
+    /// How long creating the task that was solved took, including the
+    /// retries of the request
+    pub submit_latency: Duration,
+
+    /// The number of requests sent for the result of the task, across every
+    /// submission of it
+    pub polls: u32,
+
+    /// How long the whole solve took, from the first submission to the
+    /// solution. For tasks that were resumed, it starts when they were
+    /// resumed
+    pub wall_time: Duration,
+
+    /// Every retry that happened along the way, in order
+    pub retries: Vec<Retry>,
+}
+
+/// A request or submission that was sent again after it failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Retry {
+    /// What was retried
+    pub stage: RetryStage,
+
+    /// The code of the error that caused the retry, as returned by
+    /// [`Error::code`]
+    pub error_code: Box<str>,
+}
+
+/// What a [`Retry`] was for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum RetryStage {
+    /// The request creating the task
+    Submit,
+
+    /// A request for the result of the task
+    Poll,
+
+    /// The whole task, after the workers failed to solve it
+    Resubmit,
+}
+
+impl Retry {
+    fn new(stage: RetryStage, error: &Error) -> Self {
+        Self {
+            stage,
+            error_code: error.code().into(),
+        }
+    }
+}
+
+impl SolveMetrics {
+    /// Adds the polls and retries of the earlier submissions of a task, which
+    /// were not solved, to the metrics of the one that was
+    pub(crate) fn after(mut self, earlier: Self, started: Instant) -> Self {
+        self.polls += earlier.polls;
+        self.retries.splice(..0, earlier.retries);
+        self.wall_time = started.elapsed();
+        self
+    }
+
+    /// Records that a task failed and was submitted again because of `error`
+    pub(crate) fn resubmitted(&mut self, error: &Error) {
+        self.retries.push(Retry::new(RetryStage::Resubmit, error));
+    }
+}
+
+/// Collects the [`SolveMetrics`] of a single task while it is submitted and
+/// polled
+pub struct Trace {
+    started: Instant,
+    metrics: Mutex<SolveMetrics>,
+}
+
+impl Trace {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            metrics: Mutex::default(),
+        }
+    }
+
+    pub fn submitted(&self, latency: Duration) {
+        self.lock().submit_latency = latency;
+    }
+
+    pub fn polled(&self) {
+        self.lock().polls += 1;
+    }
+
+    pub fn retried(&self, stage: RetryStage, error: &Error) {
+        self.lock().retries.push(Retry::new(stage, error));
+    }
+
+    /// The metrics collected so far, with the wall time up to now
+    pub fn metrics(&self) -> SolveMetrics {
+        SolveMetrics {
+            wall_time: self.started.elapsed(),
+            ..self.lock().clone()
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, SolveMetrics> {
+        self.metrics.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -0,0 +1,173 @@
+use std::time::Duration;
+
+/// Controls how long [`crate::CaptchaSolver`] waits for a task to be solved.
+///
+/// Options can be set for every task solved by a solver with
+/// [`crate::captcha_solver::CaptchaSolverBuilder::solve_options`] and
+/// overridden for a single task with [`crate::CaptchaSolver::solve_with`].
+/// Unset options fall back to the solver's options, and then to the values
+/// documented on each setter.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use captcha_oxide::{CaptchaSolver, captcha_solver::SolveOptions};
+///
+/// let solver = CaptchaSolver::builder()
+///     .api_key("YOUR TWOCAPTCHA API KEY")
+///     .solve_options(
+///         SolveOptions::new()
+///             .poll_interval(Duration::from_secs(3))
+///             .deadline(Duration::from_secs(120)),
+///     )
+///     .build();
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SolveOptions {
+    initial_delay: Option<Duration>,
+    poll_interval: Option<Duration>,
+    deadline: Option<Duration>,
+}
+
+/// Used when neither the call nor the solver set a deadline
+const DEFAULT_DEADLINE: Duration = Duration::from_secs(600);
+
+/// Used for resumed tasks when neither the call nor the solver set a poll
+/// interval, as there is no task to get one from
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+impl SolveOptions {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            initial_delay: None,
+            poll_interval: None,
+            deadline: None,
+        }
+    }
+
+    /// How long to wait after creating the task before asking for its result
+    /// for the first time. Defaults to [`crate::Captcha::get_timeout`]
+    #[must_use]
+    pub const fn initial_delay(mut self, initial_delay: Duration) -> Self {
+        self.initial_delay = Some(initial_delay);
+        self
+    }
+
+    /// How long to wait between two requests for the task's result while it
+    /// is still being processed. Defaults to [`crate::Captcha::get_poll_interval`]
+    #[must_use]
+    pub const fn poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = Some(poll_interval);
+        self
+    }
+
+    /// How long to wait for the task to be solved, counting from the moment
+    /// solving started, before giving up with [`crate::Error::Timeout`].
+    /// Defaults to 10 minutes. Use [`Duration::MAX`] to wait indefinitely
+    #[must_use]
+    pub const fn deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Fills the options that are not set in `self` with the ones in `fallback`
+    #[must_use]
+    pub(crate) const fn or(self, fallback: Self) -> Self {
+        Self {
+            initial_delay: match self.initial_delay {
+                Some(x) => Some(x),
+                None => fallback.initial_delay,
+            },
+            poll_interval: match self.poll_interval {
+                Some(x) => Some(x),
+                None => fallback.poll_interval,
+            },
+            deadline: match self.deadline {
+                Some(x) => Some(x),
+                None => fallback.deadline,
+            },
+        }
+    }
+
+    pub(crate) fn get_initial_delay(&self, default: Duration) -> Duration {
+        self.initial_delay.unwrap_or(default)
+    }
+
+    pub(crate) fn get_poll_interval(&self, default: Duration) -> Duration {
+        self.poll_interval.unwrap_or(default)
+    }
+
+    pub(crate) fn get_deadline(&self) -> Duration {
+        self.deadline.unwrap_or(DEFAULT_DEADLINE)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use url::Url;
+
+    use super::SolveOptions;
+    use crate::{
+        mock,
+        transport::{BoxFuture, Error, Transport},
+        CaptchaSolver,
+    };
+
+    /// Creates tasks that never finish processing
+    struct Stuck;
+
+    impl Transport for Stuck {
+        fn post_json<'a>(
+            &'a self,
+            url: &'a Url,
+            _: Vec<u8>,
+        ) -> BoxFuture<'a, Result<Vec<u8>, Error>> {
+            let body: &[u8] = match url.path() {
+                "/createTask" => br#"{ "errorId": 0, "taskId": 42 }"#,
+                _ => br#"{ "errorId": 0, "status": "processing" }"#,
+            };
+
+            Box::pin(async move { Ok(body.to_vec()) })
+        }
+    }
+
+    #[tokio::test]
+    async fn deadline() {
+        let solver = CaptchaSolver::builder()
+            .api_key("API_KEY")
+            .transport(Stuck)
+            .solve_options(
+                SolveOptions::new()
+                    .initial_delay(Duration::ZERO)
+                    .poll_interval(Duration::from_millis(10)),
+            )
+            .build();
+
+        let captcha = mock::captcha();
+        let options = SolveOptions::new().deadline(Duration::from_millis(50));
+
+        let error = solver.solve_with(&captcha, options).await.unwrap_err();
+
+        assert!(
+            matches!(error, crate::Error::Timeout { task_id } if task_id.map(u64::from) == Some(42))
+        );
+    }
+
+    #[test]
+    fn fallback() {
+        let solver = SolveOptions::new()
+            .initial_delay(Duration::from_secs(1))
+            .deadline(Duration::from_secs(60));
+        let call = SolveOptions::new().deadline(Duration::from_secs(10));
+
+        assert_eq!(
+            call.or(solver),
+            SolveOptions::new()
+                .initial_delay(Duration::from_secs(1))
+                .deadline(Duration::from_secs(10))
+        );
+    }
+}
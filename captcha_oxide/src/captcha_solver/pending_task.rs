@@ -0,0 +1,208 @@
+use std::{marker::PhantomData, sync::Arc, time::Duration};
+
+use tokio::time::{timeout_at, Instant};
+use tokio_util::sync::CancellationToken;
+
+use crate::{captcha::solution::Solution, Captcha, CaptchaSolver, Error, Result};
+
+use super::{
+    key_pool::Lease,
+    metrics::{SolveMetrics, Trace},
+    parse_task_result, Event, TaskId,
+};
+
+/// A task that was submitted to 2captcha and may not be solved yet.
+///
+/// Use [`CaptchaSolver::submit`] to create a task and get one of these, or
+/// [`CaptchaSolver::resume`] to keep polling a task created earlier, e.g. by
+/// a process that has since been restarted.
+///
+/// # Example
+/// ```no_run
+/// use captcha_oxide::{
+///     CaptchaSolver,
+///     captcha::types::normal_captcha::NormalCaptcha,
+///     Captcha,
+/// };
+///
+/// async fn example() -> captcha_oxide::Result<()> {
+///     let solver = CaptchaSolver::new("YOUR TWOCAPTCHA API KEY");
+///     let captcha = NormalCaptcha::builder().body("R0lGODlhAQABAAAAACw=").build();
+///
+///     let pending = solver.submit(&captcha).await?;
+///     let task_id = pending.task_id();
+///
+///     // Later, possibly in another process
+///     let solution = solver.resume::<NormalCaptcha>(task_id).wait().await?;
+///
+///     Ok(())
+/// }
+/// ```
+#[must_use]
+pub struct PendingTask<'s, T> {
+    solver: &'s CaptchaSolver,
+    task_id: TaskId,
+    poll_interval: Duration,
+    ready_at: Instant,
+    deadline: Option<Instant>,
+    trace: Arc<Trace>,
+
+    /// Keeps the task counted towards the concurrency cap of its key
+    _lease: Option<Lease<'s>>,
+    _task: PhantomData<fn() -> T>,
+}
+
+impl<'s, T> PendingTask<'s, T>
+where
+    T: Captcha,
+{
+    pub(crate) fn new(
+        solver: &'s CaptchaSolver,
+        task_id: TaskId,
+        poll_interval: Duration,
+        ready_at: Instant,
+        deadline: Option<Instant>,
+        trace: Trace,
+        lease: Option<Lease<'s>>,
+    ) -> Self {
+        Self {
+            solver,
+            task_id,
+            poll_interval,
+            ready_at,
+            deadline,
+            trace: Arc::new(trace),
+            _lease: lease,
+            _task: PhantomData,
+        }
+    }
+
+    /// The id of the task
+    #[must_use]
+    pub const fn task_id(&self) -> TaskId {
+        self.task_id
+    }
+
+    /// Consumes the handle, returning the id of the task so it can be stored
+    /// and resumed later
+    #[must_use]
+    pub fn into_id(self) -> TaskId {
+        self.task_id
+    }
+
+    /// Asks 2captcha for the task's result once, returning `Ok(None)` if it
+    /// is still being processed
+    ///
+    /// # Errors
+    /// This function can error if the HTTP request is not sent successfully,
+    /// if the response cannot be parsed or if the 2captcha API returns an error
+    pub async fn poll_once<'a>(&self) -> Result<Option<Solution<'a, T>>> {
+        let solution = self
+            .solver
+            .get_task_result(self.task_id, &self.trace)
+            .await?;
+
+        Ok(solution.map(|solution| self.with_metrics(solution)))
+    }
+
+    /// The metrics of the task so far. The solution returned once the task
+    /// is solved carries them too
+    #[must_use]
+    pub fn metrics(&self) -> SolveMetrics {
+        self.trace.metrics()
+    }
+
+    /// Waits until the task is solved. The task is polled at the configured
+    /// interval by the solver's background poller, along with every other
+    /// task the solver is waiting on. If the solver has a
+    /// [`super::PingbackReceiver`], the solution is fetched as soon as the
+    /// pingback for the task arrives
+    ///
+    /// # Errors
+    /// This function can error if the HTTP request is not sent successfully,
+    /// if the response cannot be parsed, if the 2captcha API returns an error
+    /// or if the task is not solved before the deadline, in which case
+    /// [`Error::Timeout`] is returned
+    pub async fn wait<'a>(&self) -> Result<Solution<'a, T>> {
+        match self.deadline {
+            Some(deadline) => timeout_at(deadline, self.wait_for_result())
+                .await
+                .map_err(|_| Error::Timeout {
+                    task_id: Some(self.task_id),
+                })?,
+            None => self.wait_for_result().await,
+        }
+    }
+
+    #[cfg(feature = "pingback")]
+    async fn wait_for_result<'a>(&self) -> Result<Solution<'a, T>> {
+        let Some(pingback) = &self.solver.pingback else {
+            return self.poll().await;
+        };
+
+        tokio::select! {
+            result = self.poll() => result,
+            () = pingback.wait(self.task_id) => match self.poll_once().await? {
+                Some(solution) => Ok(solution),
+                None => self.poll().await,
+            },
+        }
+    }
+
+    #[cfg(not(feature = "pingback"))]
+    async fn wait_for_result<'a>(&self) -> Result<Solution<'a, T>> {
+        self.poll().await
+    }
+
+    /// Waits for the background poller to find the task solved
+    async fn poll<'a>(&self) -> Result<Solution<'a, T>> {
+        let mut ready_at = self.ready_at;
+
+        loop {
+            let response = self
+                .solver
+                .poller
+                .watch(
+                    self.task_id,
+                    ready_at,
+                    self.poll_interval,
+                    self.trace.clone(),
+                )
+                .await?;
+
+            if let Some(solution) = parse_task_result(self.task_id, &response)? {
+                return Ok(self.with_metrics(solution));
+            }
+
+            ready_at = Instant::now() + self.poll_interval;
+        }
+    }
+
+    fn with_metrics<'a>(&self, mut solution: Solution<'a, T>) -> Solution<'a, T> {
+        solution.metrics = self.trace.metrics();
+        solution
+    }
+
+    /// Same as [`PendingTask::wait`], but stops polling as soon as `cancel`
+    /// is cancelled
+    ///
+    /// # Errors
+    /// Besides the errors returned by [`PendingTask::wait`], this function
+    /// returns [`Error::Cancelled`] with the task's id when `cancel` is
+    /// cancelled, after emitting an [`Event::TaskAbandoned`] for it
+    pub async fn wait_cancellable<'a>(
+        &self,
+        cancel: &CancellationToken,
+    ) -> Result<Solution<'a, T>> {
+        tokio::select! {
+            biased;
+
+            () = cancel.cancelled() => {
+                self.solver.emit(&Event::TaskAbandoned { task_id: self.task_id });
+
+                Err(Error::Cancelled { task_id: Some(self.task_id) })
+            }
+            result = self.wait() => result,
+        }
+    }
+}
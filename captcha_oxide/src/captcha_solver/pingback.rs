@@ -0,0 +1,333 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    io,
+    net::SocketAddr,
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    sync::oneshot,
+    task::JoinHandle,
+    time::{sleep, timeout},
+};
+use url::form_urlencoded;
+
+use super::TaskId;
+
+/// How many pingbacks for tasks nobody is waiting on yet are kept, for when
+/// 2captcha calls back before the response to `createTask` is processed
+const MAX_EARLY_PINGBACKS: usize = 1024;
+
+/// The largest request body the receiver reads
+const MAX_BODY_SIZE: usize = 64 * 1024;
+
+/// The most the receiver reads from a connection, request line and headers
+/// included, so a client cannot make it buffer an endless line
+const MAX_REQUEST_SIZE: u64 = 80 * 1024;
+
+/// How long a client has to send its request before it is disconnected
+const RECEIVE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long to wait before accepting connections again after failing to,
+/// e.g. because the process ran out of file descriptors
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// An HTTP server that receives the pingbacks 2captcha sends to the
+/// `callback_url` once a task is solved.
+///
+/// Pass it to [`crate::captcha_solver::CaptchaSolverBuilder::pingback`],
+/// along with the public URL that reaches it, and the solver's futures
+/// resolve as soon as the pingback for their task arrives, instead of on
+/// the next poll. The pingback only tells which task is finished, so the
+/// solution is then fetched with `getTaskResult`.
+///
+/// The receiver stops listening when it is dropped.
+///
+/// # Example
+/// ```no_run
+/// use captcha_oxide::{CaptchaSolver, captcha_solver::PingbackReceiver};
+/// use url::Url;
+///
+/// async fn example() -> Result<(), Box<dyn std::error::Error>> {
+///     let solver = CaptchaSolver::builder()
+///         .api_key("YOUR TWOCAPTCHA API KEY")
+///         .pingback(
+///             PingbackReceiver::bind("0.0.0.0:8080").await?,
+///             Url::parse("https://example.com/2captcha")?,
+///         )
+///         .build();
+///
+///     Ok(())
+/// }
+/// ```
+pub struct PingbackReceiver {
+    local_addr: SocketAddr,
+    tasks: Arc<Mutex<Tasks>>,
+    server: JoinHandle<()>,
+}
+
+#[derive(Default)]
+struct Tasks {
+    waiting: HashMap<u64, oneshot::Sender<()>>,
+    early: VecDeque<u64>,
+}
+
+impl PingbackReceiver {
+    /// Starts listening for pingbacks on `addr`
+    ///
+    /// # Errors
+    /// This function will error if the address cannot be bound
+    pub async fn bind<A>(addr: A) -> io::Result<Self>
+    where
+        A: ToSocketAddrs,
+    {
+        let listener = TcpListener::bind(addr).await?;
+        let local_addr = listener.local_addr()?;
+        let tasks = Arc::new(Mutex::new(Tasks::default()));
+
+        Ok(Self {
+            local_addr,
+            tasks: tasks.clone(),
+            server: tokio::spawn(serve(listener, tasks)),
+        })
+    }
+
+    /// The address the receiver is listening on
+    #[must_use]
+    pub const fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Waits for the pingback of the given task
+    pub(crate) async fn wait(&self, task_id: TaskId) {
+        // Pingbacks only carry the id, not the key the task was created with
+        let task_id = u64::from(task_id);
+        let receiver = {
+            let mut tasks = self.tasks.lock().unwrap_or_else(PoisonError::into_inner);
+
+            if let Some(index) = tasks.early.iter().position(|&id| id == task_id) {
+                tasks.early.remove(index);
+                return;
+            }
+
+            let (sender, receiver) = oneshot::channel();
+            tasks.waiting.retain(|_, sender| !sender.is_closed());
+            tasks.waiting.insert(task_id, sender);
+            receiver
+        };
+
+        // The sender is only dropped along with the receiver itself
+        let _ = receiver.await;
+    }
+}
+
+impl Drop for PingbackReceiver {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+async fn serve(listener: TcpListener, tasks: Arc<Mutex<Tasks>>) {
+    loop {
+        let Ok((stream, _)) = listener.accept().await else {
+            sleep(ACCEPT_BACKOFF).await;
+            continue;
+        };
+
+        let tasks = tasks.clone();
+        tokio::spawn(async move {
+            if let Ok(Ok(Some(task_id))) = timeout(RECEIVE_TIMEOUT, receive(stream)).await {
+                notify(&tasks, task_id.into());
+            }
+        });
+    }
+}
+
+fn notify(tasks: &Mutex<Tasks>, task_id: u64) {
+    let mut tasks = tasks.lock().unwrap_or_else(PoisonError::into_inner);
+
+    if let Some(sender) = tasks.waiting.remove(&task_id) {
+        let _ = sender.send(());
+        return;
+    }
+
+    if tasks.early.len() == MAX_EARLY_PINGBACKS {
+        tasks.early.pop_front();
+    }
+    tasks.early.push_back(task_id);
+}
+
+/// Reads a single pingback request and answers it, returning the id of the
+/// task it is about, if any
+async fn receive(stream: TcpStream) -> io::Result<Option<TaskId>> {
+    let mut stream = BufReader::new(stream.take(MAX_REQUEST_SIZE));
+
+    let mut line = String::new();
+    stream.read_line(&mut line).await?;
+    let query = line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|target| target.split_once('?'))
+        .map(|(_, query)| query.to_owned());
+
+    let mut content_length = 0;
+    loop {
+        line.clear();
+        if stream.read_line(&mut line).await? == 0 {
+            break;
+        }
+
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or_default();
+            }
+        }
+    }
+
+    let mut body = vec![0; content_length.min(MAX_BODY_SIZE)];
+    stream.read_exact(&mut body).await?;
+
+    let task_id = query
+        .as_deref()
+        .and_then(|query| task_id(query.as_bytes()))
+        .or_else(|| task_id(&body));
+
+    let status = if task_id.is_some() {
+        "200 OK"
+    } else {
+        "400 Bad Request"
+    };
+    let stream = stream.get_mut().get_mut();
+    stream
+        .write_all(
+            format!("HTTP/1.1 {status}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+                .as_bytes(),
+        )
+        .await?;
+    stream.flush().await?;
+
+    Ok(task_id)
+}
+
+/// Finds the task id in a pingback, sent either as a form or as JSON with an
+/// `id` or `taskId` field
+fn task_id(body: &[u8]) -> Option<TaskId> {
+    if let Ok(serde_json::Value::Object(object)) = serde_json::from_slice(body) {
+        return ["id", "taskId"]
+            .iter()
+            .filter_map(|&key| object.get(key))
+            .find_map(|id| match id {
+                serde_json::Value::Number(id) => id.as_u64(),
+                serde_json::Value::String(id) => id.parse().ok(),
+                _ => None,
+            })
+            .map(TaskId::from);
+    }
+
+    form_urlencoded::parse(body)
+        .find(|(key, _)| key == "id" || key == "taskId")
+        .and_then(|(_, id)| id.parse::<u64>().ok())
+        .map(TaskId::from)
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use captcha_oxide_testkit::{MockServer, TaskScript};
+    use serde_json::json;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpStream,
+        time::{timeout, Instant},
+    };
+    use url::Url;
+
+    use super::{task_id, PingbackReceiver, TaskId};
+    use crate::{captcha_solver::SolveOptions, mock};
+
+    #[tokio::test]
+    async fn resolves_on_pingback() {
+        let server = MockServer::start().await;
+        server.enqueue(TaskScript::new().ready(json!({ "text": "w68hp" })));
+
+        let receiver = PingbackReceiver::bind("127.0.0.1:0").await.unwrap();
+        let addr = receiver.local_addr();
+        let callback_url = Url::parse(&format!("http://{addr}/")).unwrap();
+        let solver = mock::builder(&server)
+            .pingback(receiver, callback_url)
+            .build();
+
+        // Long enough that the solution can only be fetched after the pingback
+        let options = SolveOptions::new()
+            .initial_delay(Duration::from_secs(60))
+            .poll_interval(Duration::from_secs(60));
+
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            stream
+                .write_all(b"POST / HTTP/1.1\r\nContent-Length: 24\r\n\r\nid=1&code=w68hp&extra=42")
+                .await
+                .unwrap();
+
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+            assert!(response.starts_with("HTTP/1.1 200"));
+        });
+
+        let start = Instant::now();
+        let captcha = mock::captcha();
+        let solution = timeout(Duration::from_secs(5), solver.solve_with(&captcha, options))
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(solution.solution.text, "w68hp");
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert_eq!(
+            server.requests()[0].body["callbackUrl"],
+            format!("http://{addr}/")
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_endless_lines() {
+        let receiver = PingbackReceiver::bind("127.0.0.1:0").await.unwrap();
+        let mut stream = TcpStream::connect(receiver.local_addr()).await.unwrap();
+
+        let line = vec![b'a'; usize::try_from(super::MAX_REQUEST_SIZE).unwrap()];
+        stream.write_all(&line).await.unwrap();
+
+        let mut response = String::new();
+        timeout(Duration::from_secs(5), stream.read_to_string(&mut response))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(response.starts_with("HTTP/1.1 400"));
+    }
+
+    #[test]
+    fn parses_task_ids() {
+        assert_eq!(
+            task_id(b"id=72345678901&code=w68hp"),
+            Some(TaskId::from(72_345_678_901))
+        );
+        assert_eq!(task_id(br#"{ "taskId": 42 }"#), Some(TaskId::from(42)));
+        assert_eq!(
+            task_id(br#"{ "id": "42", "code": "w68hp" }"#),
+            Some(TaskId::from(42))
+        );
+        assert_eq!(task_id(b"code=w68hp"), None);
+    }
+}
@@ -0,0 +1,286 @@
+use std::{
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+
+use futures_util::{stream::FuturesUnordered, StreamExt};
+use serde::Deserialize;
+use tokio::{
+    sync::{mpsc, oneshot},
+    time::{sleep_until, Instant},
+};
+
+use crate::{captcha_solver::error::ApiError, transport, Error, Result};
+
+use super::{
+    api::Api,
+    metrics::{RetryStage, Trace},
+    TaskId,
+};
+
+/// Polls for the results of every task a [`crate::CaptchaSolver`] is waiting
+/// on from a single background task.
+///
+/// Waiting futures register a [`Watch`] and are woken with the body of the
+/// response once their task is no longer being processed. Polls are
+/// scheduled centrally, so they go through the solver's rate limit and
+/// a thousand waiting tasks do not mean a thousand timers
+pub struct Poller {
+    api: Arc<Api>,
+    watches: Mutex<Option<mpsc::UnboundedSender<Watch>>>,
+}
+
+struct Watch {
+    task_id: TaskId,
+    next_poll: Instant,
+    poll_interval: Duration,
+    failures: u32,
+    trace: Arc<Trace>,
+    result: oneshot::Sender<Result<Vec<u8>>>,
+
+    /// The span the task is waited on in, which polls are recorded under
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+}
+
+/// The fields of a `getTaskResult` response the poller needs to know
+/// whether the task is finished, regardless of the task's solution type
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Progress {
+    #[serde(default)]
+    error_id: u16,
+    error_code: Option<String>,
+    error_description: Option<String>,
+    status: Option<String>,
+}
+
+impl Poller {
+    pub const fn new(api: Arc<Api>) -> Self {
+        Self {
+            api,
+            watches: Mutex::new(None),
+        }
+    }
+
+    /// Waits until the given task is no longer being processed, polling for
+    /// its result every `poll_interval` starting at `ready_at`, and returns
+    /// the body of the last response. Every poll and retried poll is
+    /// recorded in `trace`. Dropping the future stops the polling
+    pub async fn watch(
+        &self,
+        task_id: TaskId,
+        ready_at: Instant,
+        poll_interval: Duration,
+        trace: Arc<Trace>,
+    ) -> Result<Vec<u8>> {
+        let (sender, receiver) = oneshot::channel();
+
+        self.register(Watch {
+            task_id,
+            next_poll: ready_at,
+            poll_interval,
+            failures: 0,
+            trace,
+            result: sender,
+            #[cfg(feature = "tracing")]
+            span: tracing::Span::current(),
+        });
+
+        receiver.await.unwrap_or_else(|_| {
+            Err(
+                transport::Error::Other("The poller stopped before the task finished".into())
+                    .into(),
+            )
+        })
+    }
+
+    /// Hands `watch` to the background task, starting it if it is not
+    /// running, e.g. because the runtime it was spawned on has shut down
+    fn register(&self, watch: Watch) {
+        let mut watches = self.watches.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let watch = match watches.as_ref() {
+            Some(sender) => match sender.send(watch) {
+                Ok(()) => return,
+                Err(mpsc::error::SendError(watch)) => watch,
+            },
+            None => watch,
+        };
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        // The receiver is alive until the task below is spawned
+        let _ = sender.send(watch);
+        tokio::spawn(run(self.api.clone(), receiver));
+
+        *watches = Some(sender);
+    }
+}
+
+/// The background task. Runs until the [`Poller`] is dropped and every task
+/// it was watching has finished
+async fn run(api: Arc<Api>, mut watches: mpsc::UnboundedReceiver<Watch>) {
+    let mut scheduled = Vec::<Watch>::new();
+    let mut in_flight = FuturesUnordered::new();
+    let mut open = true;
+
+    loop {
+        scheduled.retain(|watch| !watch.result.is_closed());
+
+        if !open && scheduled.is_empty() && in_flight.is_empty() {
+            return;
+        }
+
+        let next_poll = scheduled.iter().map(|watch| watch.next_poll).min();
+
+        tokio::select! {
+            watch = watches.recv(), if open => match watch {
+                Some(watch) => scheduled.push(watch),
+                None => open = false,
+            },
+            () = sleep_until(next_poll.unwrap_or_else(Instant::now)), if next_poll.is_some() => {
+                let now = Instant::now();
+                let (due, waiting) = scheduled
+                    .into_iter()
+                    .partition(|watch| watch.next_poll <= now);
+                scheduled = waiting;
+
+                in_flight.extend(due.into_iter().map(|watch| poll(api.clone(), watch)));
+            },
+            Some((watch, response)) = in_flight.next(), if !in_flight.is_empty() => {
+                if let Some(watch) = handle(&api, watch, response) {
+                    scheduled.push(watch);
+                }
+            },
+        }
+    }
+}
+
+async fn poll(api: Arc<Api>, watch: Watch) -> (Watch, Result<Vec<u8>>) {
+    let response = api.task_result(watch.task_id);
+    #[cfg(feature = "tracing")]
+    let response = tracing::Instrument::instrument(response, watch.span.clone());
+
+    let response = response.await;
+    watch.trace.polled();
+
+    (watch, response)
+}
+
+/// Delivers the response to the waiting future if the task is finished,
+/// returning the watch if it has to be polled again
+fn handle(api: &Api, mut watch: Watch, response: Result<Vec<u8>>) -> Option<Watch> {
+    let result = response.and_then(|body| match serde_json::from_slice::<Progress>(&body)? {
+        Progress {
+            error_id,
+            error_code: Some(code),
+            error_description,
+            ..
+        } if error_id != 0 => {
+            Err(ApiError::new(&code, error_id, error_description.as_deref()).into())
+        }
+        Progress { status, .. } if status.as_deref() == Some("processing") => Ok(None),
+        _ => Ok(Some(body)),
+    });
+
+    match result {
+        Ok(Some(body)) => {
+            let _ = watch.result.send(Ok(body));
+            None
+        }
+        Ok(None) => {
+            watch.failures = 0;
+            watch.next_poll = Instant::now() + watch.poll_interval;
+            Some(watch)
+        }
+        Err(e) => {
+            watch.failures += 1;
+
+            if api.retry_policy.should_retry(&e, watch.failures) {
+                watch.trace.retried(RetryStage::Poll, &e);
+                watch.next_poll = Instant::now() + api.retry_policy.backoff(watch.failures);
+                Some(watch)
+            } else {
+                let _ = watch.result.send(Err::<_, Error>(e));
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use captcha_oxide_testkit::{MockServer, TaskScript};
+    use futures_util::future::join_all;
+    use serde_json::json;
+    use tokio::time::{sleep, timeout, Instant};
+
+    use crate::mock;
+
+    fn polls(server: &MockServer) -> usize {
+        server
+            .requests()
+            .iter()
+            .filter(|request| request.path == "/getTaskResult")
+            .count()
+    }
+
+    #[tokio::test]
+    async fn many_waiting_tasks() {
+        let server = MockServer::start().await;
+        for i in 0..20 {
+            server.enqueue(
+                TaskScript::new()
+                    .processing(i % 3)
+                    .ready(json!({ "text": i.to_string() })),
+            );
+        }
+
+        let solver = mock::solver(&server);
+        let captchas: Vec<_> = (0..20).map(|_| mock::captcha()).collect();
+        let solutions = join_all(captchas.iter().map(|captcha| solver.solve(captcha))).await;
+
+        assert!(solutions.iter().all(Result::is_ok));
+        assert_eq!(polls(&server), (0..20).map(|i| i % 3 + 1).sum::<usize>());
+    }
+
+    #[tokio::test]
+    async fn dropping_the_future_stops_polling() {
+        let server = MockServer::start().await;
+        server.enqueue(TaskScript::new());
+
+        let solver = mock::solver(&server);
+        let pending = solver.submit(&mock::captcha()).await.unwrap();
+
+        assert!(timeout(Duration::from_millis(50), pending.wait())
+            .await
+            .is_err());
+
+        sleep(Duration::from_millis(20)).await;
+        let polled = polls(&server);
+        sleep(Duration::from_millis(50)).await;
+
+        assert_eq!(polls(&server), polled);
+    }
+
+    #[tokio::test]
+    async fn polls_share_the_rate_limit() {
+        let server = MockServer::start().await;
+        server.enqueue(TaskScript::new().ready(json!({ "text": "w68hp" })));
+        server.enqueue(TaskScript::new().ready(json!({ "text": "w68hp" })));
+
+        let solver = mock::builder(&server)
+            .rate_limit(20, Duration::from_secs(1))
+            .build();
+        let (first, second) = (mock::captcha(), mock::captcha());
+
+        let start = Instant::now();
+        let (first, second) = tokio::join!(solver.solve(&first), solver.solve(&second));
+
+        assert!(first.is_ok() && second.is_ok());
+        assert_eq!(server.requests().len(), 4);
+        assert!(start.elapsed() >= Duration::from_millis(150));
+    }
+}
@@ -0,0 +1,21 @@
+/// The version of the 2captcha API a [`crate::CaptchaSolver`] talks to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum Protocol {
+    /// The JSON API at `createTask` and `getTaskResult`
+    #[default]
+    V2,
+
+    /// The legacy API at `in.php` and `res.php`, which takes forms. Use it
+    /// with services that only implement this version of the API.
+    ///
+    /// Tasks of types that have no `in.php` method, such as grid and
+    /// coordinates captchas, are refused with
+    /// [`super::error::Error::TaskNotSupported`] without sending them, and
+    /// as `res.php` does not return them, the IP and times of the
+    /// solutions are not the real ones. When it does not return the price
+    /// either, the cost of the solution is `None` and the task is not
+    /// counted towards any spend. The legacy API is reached through the
+    /// URL set with [`super::CaptchaSolverBuilder::legacy_api_url`]
+    V1,
+}
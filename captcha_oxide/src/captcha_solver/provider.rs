@@ -0,0 +1,289 @@
+use serde_json::Value;
+use url::Url;
+
+use crate::{two_captcha::create_task::SoftId, Captcha, Result, SOFT_ID};
+
+use super::{builder::with_trailing_slash, DEFAULT_API_URL, DEFAULT_LEGACY_API_URL};
+
+/// A solving service that implements the 2captcha API, along with the few
+/// things in which it differs from 2captcha.
+///
+/// Services that copy the `createTask` API usually differ in their base URL,
+/// in the name of the field that identifies the software sending the task,
+/// and in the names of a few task types and error codes. [`Provider`]
+/// translates the requests sent to the service and the error codes it
+/// answers with, so [`crate::CaptchaSolver`] and the errors it returns work
+/// the same way regardless of the service.
+///
+/// # Example
+/// ```
+/// use captcha_oxide::{CaptchaSolver, captcha_solver::Provider};
+/// use url::Url;
+///
+/// let provider = Provider::new(Url::parse("https://api.example.com/")?)
+///     .soft_id("appId", 1234)
+///     .task_type("FunCaptchaTaskProxyless", "ArkoseTaskProxyless")
+///     .error_code("ERROR_NO_FUNDS", "ERROR_ZERO_BALANCE");
+///
+/// let solver = CaptchaSolver::builder()
+///     .api_key("YOUR API KEY")
+///     .provider(provider)
+///     .build();
+/// # Ok::<_, url::ParseError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Provider {
+    api_url: Url,
+    legacy_api_url: Url,
+    soft_id: Option<(Box<str>, u16)>,
+    task_types: Vec<(Box<str>, Box<str>)>,
+    error_codes: Vec<(Box<str>, Box<str>)>,
+}
+
+impl Default for Provider {
+    fn default() -> Self {
+        Self::two_captcha()
+    }
+}
+
+impl Provider {
+    /// 2captcha, at `https://api.2captcha.com/`
+    #[must_use]
+    pub fn two_captcha() -> Self {
+        Self {
+            api_url: DEFAULT_API_URL.clone(),
+            legacy_api_url: DEFAULT_LEGACY_API_URL.clone(),
+            soft_id: Some(("softId".into(), SOFT_ID)),
+            task_types: Vec::new(),
+            error_codes: Vec::new(),
+        }
+    }
+
+    /// rucaptcha, at `https://api.rucaptcha.com/`, which shares accounts
+    /// and task types with 2captcha
+    ///
+    /// # Panics
+    /// Never, as the URLs are valid
+    #[must_use]
+    pub fn rucaptcha() -> Self {
+        Self {
+            api_url: Url::parse("https://api.rucaptcha.com/").unwrap(),
+            legacy_api_url: Url::parse("https://rucaptcha.com/").unwrap(),
+            ..Self::two_captcha()
+        }
+    }
+
+    /// Any other service compatible with the `createTask` API at `api_url`.
+    /// No soft id is sent, and the legacy API is assumed to be at the same
+    /// URL until [`Provider::legacy_api_url`] is called. A trailing slash is
+    /// added to the URL the same way as in
+    /// [`super::CaptchaSolverBuilder::api_url`]
+    #[must_use]
+    pub fn new(api_url: Url) -> Self {
+        let api_url = with_trailing_slash(api_url);
+
+        Self {
+            legacy_api_url: api_url.clone(),
+            api_url,
+            soft_id: None,
+            task_types: Vec::new(),
+            error_codes: Vec::new(),
+        }
+    }
+
+    /// The base URL of the service's legacy `in.php`/`res.php` API, to which
+    /// a trailing slash is added like to the one given to [`Provider::new`]
+    #[must_use]
+    pub fn legacy_api_url(mut self, legacy_api_url: Url) -> Self {
+        self.legacy_api_url = with_trailing_slash(legacy_api_url);
+        self
+    }
+
+    /// Sends `id` in the `field` of every `createTask` request, as the id of
+    /// the software that created the task
+    #[must_use]
+    pub fn soft_id<T>(mut self, field: T, id: u16) -> Self
+    where
+        T: Into<Box<str>>,
+    {
+        self.soft_id = Some((field.into(), id));
+        self
+    }
+
+    /// Does not send a soft id
+    #[must_use]
+    pub fn without_soft_id(mut self) -> Self {
+        self.soft_id = None;
+        self
+    }
+
+    /// Sends tasks of the 2captcha type `name` with the type `provider_name`
+    /// instead. Proxied and proxyless variants are different types
+    #[must_use]
+    pub fn task_type<T, U>(mut self, name: T, provider_name: U) -> Self
+    where
+        T: Into<Box<str>>,
+        U: Into<Box<str>>,
+    {
+        self.task_types.push((name.into(), provider_name.into()));
+        self
+    }
+
+    /// Treats the error code `provider_code` as the 2captcha error code
+    /// `code`, so it is mapped to the same [`super::error::Error`] variant
+    #[must_use]
+    pub fn error_code<T, U>(mut self, provider_code: T, code: U) -> Self
+    where
+        T: Into<Box<str>>,
+        U: Into<Box<str>>,
+    {
+        self.error_codes.push((provider_code.into(), code.into()));
+        self
+    }
+
+    /// The base URLs of the `createTask` API and the legacy API
+    pub(super) const fn urls(&self) -> (&Url, &Url) {
+        (&self.api_url, &self.legacy_api_url)
+    }
+
+    pub(super) fn soft_id_field(&self) -> Option<SoftId<'_>> {
+        self.soft_id
+            .as_ref()
+            .map(|(field, id)| SoftId { field, id: *id })
+    }
+
+    pub(super) fn soft_id_value(&self) -> Option<u16> {
+        self.soft_id.as_ref().map(|(_, id)| *id)
+    }
+
+    /// The JSON of `task` with its type renamed, or `None` if the provider
+    /// uses the same name as 2captcha
+    pub(super) fn rename_task<T>(&self, task: &T) -> Result<Option<Value>>
+    where
+        T: Captcha,
+    {
+        let Some(name) = find(&self.task_types, task.get_task_type()) else {
+            return Ok(None);
+        };
+
+        let mut task = serde_json::to_value(task)?;
+        task["type"] = name.into();
+        Ok(Some(task))
+    }
+
+    /// Replaces the error code in the body of a response with the 2captcha
+    /// one it is mapped to, if any. The code is in `errorCode` for the
+    /// `createTask` API, and in `request` for the legacy API
+    pub(super) fn translate_error(&self, body: Vec<u8>) -> Vec<u8> {
+        if self.error_codes.is_empty() {
+            return body;
+        }
+
+        let Ok(mut response) = serde_json::from_slice::<Value>(&body) else {
+            return body;
+        };
+
+        let field = if response.get("errorCode").is_some() {
+            "errorCode"
+        } else if response.get("status") == Some(&Value::from(0)) {
+            "request"
+        } else {
+            return body;
+        };
+
+        let Some(code) = response[field]
+            .as_str()
+            .and_then(|code| find(&self.error_codes, code))
+        else {
+            return body;
+        };
+
+        response[field] = code.into();
+        serde_json::to_vec(&response).unwrap_or(body)
+    }
+}
+
+fn find<'a>(names: &'a [(Box<str>, Box<str>)], name: &str) -> Option<&'a str> {
+    names
+        .iter()
+        .find(|(from, _)| &**from == name)
+        .map(|(_, to)| &**to)
+}
+
+#[cfg(test)]
+mod test {
+    use captcha_oxide_testkit::{Fault, MockServer, TaskScript};
+    use serde_json::json;
+
+    use url::Url;
+
+    use super::Provider;
+    use crate::{captcha_solver::RetryPolicy, mock, Error};
+
+    #[test]
+    fn keeps_the_path_of_the_urls() {
+        let provider = Provider::new(Url::parse("https://proxy.example.com/2captcha").unwrap())
+            .legacy_api_url(Url::parse("https://proxy.example.com/legacy").unwrap());
+
+        assert_eq!(
+            provider.api_url.join("createTask").unwrap().as_str(),
+            "https://proxy.example.com/2captcha/createTask"
+        );
+        assert_eq!(
+            provider.legacy_api_url.join("res.php").unwrap().as_str(),
+            "https://proxy.example.com/legacy/res.php"
+        );
+    }
+
+    #[tokio::test]
+    async fn translates_requests() {
+        let server = MockServer::start().await;
+        server.enqueue(TaskScript::new().ready(json!({ "text": "w68hp" })));
+
+        let provider = Provider::new(server.url())
+            .soft_id("appId", 42)
+            .task_type("ImageToTextTask", "ImageToTextTaskV2");
+        let solver = mock::builder(&server).provider(provider).build();
+
+        let solution = mock::solved(solver.solve(&mock::captcha()).await).unwrap();
+
+        assert_eq!(solution.solution.text, "w68hp");
+        server.assert_last_task(&json!({ "type": "ImageToTextTaskV2" }));
+
+        let request = &server.requests()[0].body;
+        assert_eq!(request["appId"], json!(42));
+        assert!(request.get("softId").is_none());
+    }
+
+    #[tokio::test]
+    async fn translates_error_codes() {
+        let server = MockServer::start().await;
+        server.inject("createTask", Fault::ErrorCode("ERROR_NO_FUNDS"), 1);
+
+        let provider =
+            Provider::new(server.url()).error_code("ERROR_NO_FUNDS", "ERROR_ZERO_BALANCE");
+        let solver = mock::builder(&server)
+            .provider(provider)
+            .retry_policy(RetryPolicy::none())
+            .build();
+
+        let error = solver.solve(&mock::captcha()).await.unwrap_err();
+
+        assert!(matches!(
+            error,
+            Error::TwoCaptchaError(ref e) if e.code() == "ERROR_ZERO_BALANCE"
+        ));
+    }
+
+    #[test]
+    fn two_captcha_is_the_default() {
+        let provider = Provider::default();
+
+        assert_eq!(provider.urls().0.as_str(), "https://api.2captcha.com/");
+        assert_eq!(provider.soft_id_value(), Some(4143));
+        assert_eq!(
+            Provider::rucaptcha().urls().0.as_str(),
+            "https://api.rucaptcha.com/"
+        );
+    }
+}
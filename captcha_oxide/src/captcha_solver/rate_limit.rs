@@ -0,0 +1,60 @@
+use std::{
+    sync::{Mutex, PoisonError},
+    time::Duration,
+};
+
+use tokio::time::{sleep_until, Instant};
+
+/// Spaces out requests evenly so no more than a given number of them are
+/// sent in any period. Set with
+/// [`crate::captcha_solver::CaptchaSolverBuilder::rate_limit`]
+#[derive(Debug)]
+pub struct RateLimit {
+    interval: Duration,
+    next_slot: Mutex<Instant>,
+}
+
+impl RateLimit {
+    pub fn new(requests: u32, period: Duration) -> Self {
+        Self {
+            interval: period / requests.max(1),
+            next_slot: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Waits until the next request may be sent
+    pub async fn acquire(&self) {
+        let slot = {
+            let mut next_slot = self
+                .next_slot
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            let slot = (*next_slot).max(Instant::now());
+            *next_slot = slot + self.interval;
+            slot
+        };
+
+        sleep_until(slot).await;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use tokio::time::Instant;
+
+    use super::RateLimit;
+
+    #[tokio::test(start_paused = true)]
+    async fn spaces_requests() {
+        let rate_limit = RateLimit::new(4, Duration::from_secs(1));
+        let start = Instant::now();
+
+        for _ in 0..5 {
+            rate_limit.acquire().await;
+        }
+
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+    }
+}
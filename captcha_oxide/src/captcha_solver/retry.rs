@@ -0,0 +1,334 @@
+use std::{
+    collections::hash_map::RandomState,
+    future::Future,
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+use tokio::time::sleep;
+
+use crate::{captcha_solver::error::ErrorClass, Error, Result};
+
+/// Controls how [`crate::CaptchaSolver`] retries requests that fail.
+///
+/// The policy is applied to every request sent to the API: task creation,
+/// each poll for a task's result, balance checks and reports. A request is
+/// retried with exponential backoff while it fails with an error whose
+/// [`ErrorClass`] is listed in [`RetryPolicy::retry_on`], up to
+/// [`RetryPolicy::max_attempts`] attempts.
+///
+/// Requests that create tasks are only retried when the API certainly did
+/// not create the task: the connection could not be made, the API turned
+/// the request away with a `429` or a `503`, or it answered with an error
+/// code such as `ERROR_NO_SLOT_AVAILABLE`. A request that timed out or whose
+/// response could not be parsed may have created a task that is already
+/// being charged for, so it is not sent again.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use captcha_oxide::{
+///     CaptchaSolver,
+///     captcha_solver::{error::ErrorClass, RetryPolicy},
+/// };
+///
+/// let solver = CaptchaSolver::builder()
+///     .api_key("YOUR TWOCAPTCHA API KEY")
+///     .retry_policy(
+///         RetryPolicy::default()
+///             .max_attempts(5)
+///             .initial_backoff(Duration::from_millis(500))
+///             .retry_on([ErrorClass::Transient, ErrorClass::Throttled])
+///             .resubmit_unsolvable(true),
+///     )
+///     .build();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: u32,
+    jitter: bool,
+    retry_on: Box<[ErrorClass]>,
+    resubmit_unsolvable: bool,
+}
+
+impl Default for RetryPolicy {
+    /// Up to 3 attempts for [`ErrorClass::Transient`] and [`ErrorClass::Throttled`]
+    /// errors, waiting 1 second before the first retry and doubling the wait
+    /// on every retry, with jitter, up to 30 seconds
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2,
+            jitter: true,
+            retry_on: Box::new([ErrorClass::Transient, ErrorClass::Throttled]),
+            resubmit_unsolvable: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries, so every request fails on its first error
+    #[must_use]
+    pub fn none() -> Self {
+        Self::default().max_attempts(1)
+    }
+
+    /// The maximum number of times a request is sent, including the first
+    /// attempt. Values lower than 1 are treated as 1
+    #[must_use]
+    pub const fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = if max_attempts == 0 { 1 } else { max_attempts };
+        self
+    }
+
+    /// How long to wait before the first retry
+    #[must_use]
+    pub const fn initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    /// The longest the policy will wait between two attempts
+    #[must_use]
+    pub const fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// By how much the wait is multiplied after every retry
+    #[must_use]
+    pub const fn multiplier(mut self, multiplier: u32) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// Whether to randomize each wait to somewhere between half of it and
+    /// all of it, so many clients failing at once do not retry in lockstep
+    #[must_use]
+    pub const fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// The classes of errors that cause a request to be retried
+    #[must_use]
+    pub fn retry_on<I>(mut self, classes: I) -> Self
+    where
+        I: IntoIterator<Item = ErrorClass>,
+    {
+        self.retry_on = classes.into_iter().collect();
+        self
+    }
+
+    /// Whether to submit the task again when the workers fail to solve it,
+    /// which 2captcha does not charge for. Resubmissions count towards
+    /// [`RetryPolicy::max_attempts`]
+    #[must_use]
+    pub const fn resubmit_unsolvable(mut self, resubmit_unsolvable: bool) -> Self {
+        self.resubmit_unsolvable = resubmit_unsolvable;
+        self
+    }
+
+    /// Whether a request that failed with `error` on its `attempt`-th try
+    /// should be sent again
+    pub(crate) fn should_retry(&self, error: &Error, attempt: u32) -> bool {
+        attempt < self.max_attempts && self.retry_on.contains(&error.class())
+    }
+
+    /// Whether a task whose `attempt`-th submission failed with `error`
+    /// should be submitted again
+    pub(crate) fn should_resubmit(&self, error: &Error, attempt: u32) -> bool {
+        self.resubmit_unsolvable
+            && attempt < self.max_attempts
+            && error.class() == ErrorClass::Unsolvable
+    }
+
+    /// How long to wait after the `attempt`-th try failed
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let backoff = self
+            .multiplier
+            .checked_pow(attempt.saturating_sub(1))
+            .and_then(|factor| self.initial_backoff.checked_mul(factor))
+            .map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff));
+
+        if self.jitter {
+            jitter(backoff)
+        } else {
+            backoff
+        }
+    }
+
+    /// Runs `request` until it succeeds or fails with an error this policy
+    /// does not retry, sleeping between attempts
+    pub(crate) async fn run<F, Fut, T>(&self, request: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        self.run_traced(request, |_| {}).await
+    }
+
+    /// Same as [`RetryPolicy::run`], calling `on_retry` with the error of
+    /// every attempt that is retried
+    pub(crate) async fn run_traced<F, Fut, T, R>(&self, request: F, on_retry: R) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+        R: FnMut(&Error),
+    {
+        self.run_while(request, on_retry, |_| true).await
+    }
+
+    /// Same as [`RetryPolicy::run_traced`], for requests that create tasks,
+    /// which are only retried when the error is a rejection of the request
+    pub(crate) async fn run_submit<F, Fut, T, R>(&self, request: F, on_retry: R) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+        R: FnMut(&Error),
+    {
+        self.run_while(request, on_retry, Error::is_rejection).await
+    }
+
+    async fn run_while<F, Fut, T, R, P>(
+        &self,
+        mut request: F,
+        mut on_retry: R,
+        retryable: P,
+    ) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+        R: FnMut(&Error),
+        P: Fn(&Error) -> bool,
+    {
+        let mut attempt = 1;
+
+        loop {
+            match request().await {
+                Err(e) if retryable(&e) && self.should_retry(&e, attempt) => {
+                    on_retry(&e);
+                    sleep(self.backoff(attempt)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Picks a duration between half of `duration` and `duration`. The standard
+/// library seeds every [`RandomState`] randomly, which is plenty for jitter
+fn jitter(duration: Duration) -> Duration {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(duration.as_nanos());
+    let permille = u32::try_from(hasher.finish() % 1001).unwrap_or_default();
+
+    duration / 2 + duration / 2000 * permille
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        sync::atomic::{AtomicU32, Ordering},
+        time::Duration,
+    };
+
+    use url::Url;
+
+    use super::RetryPolicy;
+    use crate::{
+        captcha_solver::error::ErrorClass,
+        transport::{BoxFuture, Error, Transport},
+        CaptchaSolver,
+    };
+
+    /// Fails with a `503` the first `failures` times it is called
+    struct Flaky {
+        failures: u32,
+        calls: AtomicU32,
+    }
+
+    impl Transport for Flaky {
+        fn post_json<'a>(
+            &'a self,
+            _: &'a Url,
+            _: Vec<u8>,
+        ) -> BoxFuture<'a, Result<Vec<u8>, Error>> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            let failures = self.failures;
+
+            Box::pin(async move {
+                if call < failures {
+                    Err(Error::Status(503))
+                } else {
+                    Ok(br#"{ "errorId": 0, "balance": 1.5 }"#.to_vec())
+                }
+            })
+        }
+    }
+
+    fn solver(failures: u32, policy: RetryPolicy) -> CaptchaSolver {
+        CaptchaSolver::builder()
+            .api_key("API_KEY")
+            .transport(Flaky {
+                failures,
+                calls: AtomicU32::new(0),
+            })
+            .retry_policy(policy.initial_backoff(Duration::from_millis(1)))
+            .build()
+    }
+
+    #[tokio::test]
+    async fn retries_transient_errors() {
+        let solver = solver(2, RetryPolicy::default());
+
+        assert!(solver.get_balance().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_attempts() {
+        let solver = solver(3, RetryPolicy::default());
+
+        let error = solver.get_balance().await.unwrap_err();
+
+        assert_eq!(error.class(), ErrorClass::Transient);
+    }
+
+    #[tokio::test]
+    async fn only_retries_configured_classes() {
+        let solver = solver(1, RetryPolicy::default().retry_on([ErrorClass::Throttled]));
+
+        assert!(solver.get_balance().await.is_err());
+    }
+
+    #[test]
+    fn exponential_backoff() {
+        let policy = RetryPolicy::default()
+            .jitter(false)
+            .max_backoff(Duration::from_secs(5));
+
+        assert_eq!(policy.backoff(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff(2), Duration::from_secs(2));
+        assert_eq!(policy.backoff(3), Duration::from_secs(4));
+        assert_eq!(policy.backoff(4), Duration::from_secs(5));
+        assert_eq!(policy.backoff(100), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn jittered_backoff() {
+        let policy = RetryPolicy::default();
+
+        for attempt in 1..10 {
+            let max = policy.clone().jitter(false).backoff(attempt);
+            let backoff = policy.backoff(attempt);
+
+            assert!(backoff >= max / 2 && backoff <= max);
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! Helpers for the `tracing` spans of the solver, which are only emitted
+//! with the `tracing` feature. The spans never record the arguments of the
+//! functions they wrap, so API keys and proxy credentials are left out
+
+use crate::Result;
+
+/// Records the given fields on the current span. Does nothing without the
+/// `tracing` feature, in which case the values are not evaluated
+macro_rules! record {
+    ($($field:ident = $value:expr),+ $(,)?) => {
+        #[cfg(feature = "tracing")]
+        {
+            let span = tracing::Span::current();
+            $(span.record(stringify!($field), $value);)+
+        }
+    };
+}
+
+/// Records the code of the error in `result`, if any, on the current span
+#[cfg_attr(not(feature = "tracing"), allow(clippy::missing_const_for_fn))]
+pub fn record_error<T>(result: Result<T>) -> Result<T> {
+    #[cfg(feature = "tracing")]
+    if let Err(e) = &result {
+        record!(error_code = e.code());
+    }
+
+    result
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod test {
+    use std::{
+        fmt::{Debug, Write},
+        sync::{Arc, Mutex},
+    };
+
+    use captcha_oxide_testkit::{MockServer, TaskScript};
+    use serde_json::json;
+    use tracing::{
+        field::{Field, Visit},
+        span::{Attributes, Id, Record},
+        Event, Metadata, Subscriber,
+    };
+    use tracing_core::span::Current;
+
+    use crate::{captcha::solution::Status, mock};
+
+    /// Keeps the name of every span along with the fields recorded on it.
+    /// Only meant for a single threaded runtime, as the spans that are
+    /// entered are tracked on a single stack
+    #[derive(Default, Clone)]
+    struct Recorder {
+        spans: Arc<Mutex<Vec<(&'static Metadata<'static>, String)>>>,
+        entered: Arc<Mutex<Vec<Id>>>,
+    }
+
+    struct Fields<'a>(&'a mut String);
+
+    impl Visit for Fields<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            let _ = write!(self.0, " {}={value:?}", field.name());
+        }
+    }
+
+    impl Recorder {
+        /// The fields recorded on every span named `name`
+        fn fields(&self, name: &str) -> Vec<String> {
+            self.spans
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(span, _)| span.name() == name)
+                .map(|(_, fields)| fields.clone())
+                .collect()
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut fields = String::new();
+            span.record(&mut Fields(&mut fields));
+
+            let mut spans = self.spans.lock().unwrap();
+            spans.push((span.metadata(), fields));
+
+            Id::from_u64(u64::try_from(spans.len()).unwrap())
+        }
+
+        fn record(&self, span: &Id, values: &Record<'_>) {
+            let index = usize::try_from(span.into_u64()).unwrap() - 1;
+            values.record(&mut Fields(&mut self.spans.lock().unwrap()[index].1));
+        }
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, _: &Event<'_>) {}
+
+        fn enter(&self, span: &Id) {
+            self.entered.lock().unwrap().push(span.clone());
+        }
+
+        fn exit(&self, _: &Id) {
+            self.entered.lock().unwrap().pop();
+        }
+
+        fn current_span(&self) -> Current {
+            let Some(span) = self.entered.lock().unwrap().last().cloned() else {
+                return Current::none();
+            };
+            let index = usize::try_from(span.into_u64()).unwrap() - 1;
+
+            Current::new(span, self.spans.lock().unwrap()[index].0)
+        }
+    }
+
+    #[tokio::test]
+    async fn records_spans_without_secrets() {
+        let server = MockServer::start().await;
+        server.enqueue(
+            TaskScript::new()
+                .processing(1)
+                .ready(json!({ "text": "w68hp" })),
+        );
+        server.enqueue(TaskScript::rejected("ERROR_ZERO_BALANCE"));
+
+        let recorder = Recorder::default();
+        let _guard = tracing::subscriber::set_default(recorder.clone());
+
+        let solver = mock::builder(&server).api_key("SECRET_KEY").build();
+        let captcha = mock::captcha();
+
+        let solution = mock::solved(solver.solve(&captcha).await).unwrap();
+        let task_id = solution.task_id();
+        solver.report(solution, Status::Correct).await.unwrap();
+        assert!(solver.solve(&captcha).await.is_err());
+
+        let solve = recorder.fields("solve");
+        assert!(solve[0].contains("task_type=\"ImageToTextTask\""));
+        assert!(solve[0].contains(&format!("task_id={task_id}")));
+        assert!(solve[0].contains("polls=2"));
+        assert!(solve[1].contains("error_code=\"ERROR_ZERO_BALANCE\""));
+
+        assert_eq!(recorder.fields("create_task").len(), 2);
+        assert_eq!(recorder.fields("get_task_result").len(), 2);
+        assert!(recorder.fields("report")[0].contains(&format!("task_id={task_id}")));
+
+        assert!(recorder
+            .spans
+            .lock()
+            .unwrap()
+            .iter()
+            .all(|(_, fields)| !fields.contains("SECRET_KEY")));
+    }
+}
@@ -0,0 +1,235 @@
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    sync::{Mutex, PoisonError},
+    time::Duration,
+};
+
+use rust_decimal::Decimal;
+
+use crate::{Error, Money};
+
+/// How many of the latest solve times of each task type are kept to compute
+/// percentiles from
+const WINDOW: usize = 1000;
+
+/// Statistics about the solves of a [`crate::CaptchaSolver`], by the
+/// 2captcha name of the task type, e.g. `ImageToTextTask`. Get them with
+/// [`crate::CaptchaSolver::stats`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SolveStats {
+    pub by_task_type: BTreeMap<Box<str>, TaskStats>,
+}
+
+/// Statistics about the solves of a single task type
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TaskStats {
+    /// The number of tasks that were solved
+    pub solved: u64,
+
+    /// The number of solves that failed, by the code of the error they
+    /// failed with, as returned by [`Error::code`]. Cancelled solves are not
+    /// counted
+    pub failures: BTreeMap<Box<str>, u64>,
+
+    /// The median wall time of the latest solves
+    pub p50: Option<Duration>,
+
+    /// The 95th percentile of the wall time of the latest solves
+    pub p95: Option<Duration>,
+
+    /// The average cost of a solved task, among those whose cost is known
+    pub average_cost: Option<Money>,
+}
+
+impl TaskStats {
+    /// The number of solves that failed
+    #[must_use]
+    pub fn failed(&self) -> u64 {
+        self.failures.values().sum()
+    }
+
+    /// The share of solves that failed, between 0 and 1
+    #[must_use]
+    pub fn failure_rate(&self) -> f64 {
+        self.rate(self.failed())
+    }
+
+    /// The share of solves that failed with the error `code`, between 0 and 1
+    #[must_use]
+    pub fn failure_rate_of(&self, code: &str) -> f64 {
+        self.rate(self.failures.get(code).copied().unwrap_or_default())
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn rate(&self, count: u64) -> f64 {
+        match self.solved + self.failed() {
+            0 => 0.0,
+            total => count as f64 / total as f64,
+        }
+    }
+}
+
+/// Aggregates the outcome of every solve of a [`crate::CaptchaSolver`]
+#[derive(Default)]
+pub struct StatsCollector(Mutex<HashMap<Box<str>, Samples>>);
+
+#[derive(Default)]
+struct Samples {
+    solve_times: VecDeque<Duration>,
+    solved: u64,
+
+    /// How many of the solved tasks had a known cost, which `spent` sums
+    priced: u64,
+    spent: Money,
+    failures: BTreeMap<Box<str>, u64>,
+}
+
+impl StatsCollector {
+    pub fn solved(&self, task_type: &str, wall_time: Duration, cost: Option<Money>) {
+        self.with(task_type, |samples| {
+            if samples.solve_times.len() == WINDOW {
+                samples.solve_times.pop_front();
+            }
+
+            samples.solve_times.push_back(wall_time);
+            samples.solved += 1;
+            if let Some(cost) = cost {
+                samples.priced += 1;
+                samples.spent += cost;
+            }
+        });
+    }
+
+    pub fn failed(&self, task_type: &str, error: &Error) {
+        if matches!(error, Error::Cancelled { .. }) {
+            return;
+        }
+
+        self.with(task_type, |samples| {
+            *samples.failures.entry(error.code().into()).or_default() += 1;
+        });
+    }
+
+    pub fn snapshot(&self) -> SolveStats {
+        let by_task_type = self
+            .lock()
+            .iter()
+            .map(|(task_type, samples)| (task_type.clone(), samples.stats()))
+            .collect();
+
+        SolveStats { by_task_type }
+    }
+
+    pub fn reset(&self) {
+        self.lock().clear();
+    }
+
+    fn with(&self, task_type: &str, f: impl FnOnce(&mut Samples)) {
+        let mut tasks = self.lock();
+
+        match tasks.get_mut(task_type) {
+            Some(samples) => f(samples),
+            None => f(tasks.entry(task_type.into()).or_default()),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<Box<str>, Samples>> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Samples {
+    fn stats(&self) -> TaskStats {
+        let mut solve_times: Vec<_> = self.solve_times.iter().copied().collect();
+        solve_times.sort_unstable();
+
+        TaskStats {
+            solved: self.solved,
+            failures: self.failures.clone(),
+            p50: percentile(&solve_times, 50),
+            p95: percentile(&solve_times, 95),
+            average_cost: (self.priced > 0)
+                .then(|| Money::from(self.spent.as_decimal() / Decimal::from(self.priced))),
+        }
+    }
+}
+
+/// The nearest-rank `percent`th percentile of the sorted `values`
+fn percentile(values: &[Duration], percent: usize) -> Option<Duration> {
+    let rank = (values.len() * percent).checked_sub(1)? / 100;
+
+    values.get(rank).copied()
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use captcha_oxide_testkit::{Fault, MockServer, TaskScript};
+    use serde_json::json;
+
+    use super::percentile;
+    use crate::{captcha_solver::RetryStage, mock, Money};
+
+    #[test]
+    fn nearest_rank_percentiles() {
+        let values: Vec<_> = (1..=20).map(Duration::from_secs).collect();
+
+        assert_eq!(percentile(&values, 50), Some(Duration::from_secs(10)));
+        assert_eq!(percentile(&values, 95), Some(Duration::from_secs(19)));
+        assert_eq!(percentile(&values[..1], 95), Some(Duration::from_secs(1)));
+        assert_eq!(percentile(&[], 50), None);
+    }
+
+    #[tokio::test]
+    async fn records_metrics_on_the_solution() {
+        let server = MockServer::start().await;
+        server.enqueue(
+            TaskScript::new()
+                .processing(2)
+                .ready(json!({ "text": "w68hp" })),
+        );
+        server.inject("createTask", Fault::Status(503), 1);
+
+        let solution = mock::solved(mock::solver(&server).solve(&mock::captcha()).await).unwrap();
+        let metrics = &solution.metrics;
+
+        assert_eq!(metrics.polls, 3);
+        assert_eq!(metrics.retries.len(), 1);
+        assert_eq!(metrics.retries[0].stage, RetryStage::Submit);
+        assert_eq!(&*metrics.retries[0].error_code, "HTTP_ERROR");
+        assert!(metrics.wall_time >= metrics.submit_latency);
+    }
+
+    #[tokio::test]
+    async fn aggregates_by_task_type() {
+        let server = MockServer::start().await;
+        for cost in ["0.001", "0.002"] {
+            server.enqueue(
+                TaskScript::new()
+                    .ready(json!({ "text": "w68hp" }))
+                    .cost(cost),
+            );
+        }
+        server.enqueue(TaskScript::new().error("ERROR_CAPTCHA_UNSOLVABLE"));
+
+        let solver = mock::solver(&server);
+        for _ in 0..3 {
+            let _ = solver.solve(&mock::captcha()).await;
+        }
+
+        let stats = solver.stats();
+        let image = &stats.by_task_type["ImageToTextTask"];
+
+        assert_eq!(image.solved, 2);
+        assert_eq!(image.failed(), 1);
+        assert!((image.failure_rate_of("ERROR_CAPTCHA_UNSOLVABLE") - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(image.average_cost, Some(Money::new(15, 4)));
+        assert!(image.p50 <= image.p95);
+
+        solver.reset_stats();
+        assert!(solver.stats().by_task_type.is_empty());
+    }
+}
@@ -0,0 +1,119 @@
+use std::{fmt::Display, num::ParseIntError};
+
+use serde::{Deserialize, Serialize};
+
+/// The id 2captcha assigned to a task when it was created, along with a
+/// fingerprint of the API key it was created with.
+///
+/// The task is polled and reported with that key, even if the keys of the
+/// solver were reordered in the meantime. It can be stored and used later
+/// to resume polling with [`crate::CaptchaSolver::resume`]. Ids without a
+/// key, such as those made from a plain number, serialize as that number
+/// and are used with the primary key of the solver. The others serialize
+/// as `{ "id": 72345678901, "key": "af63dc4c8601ec8c" }`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "Repr", into = "Repr")]
+pub struct TaskId {
+    id: u64,
+    key: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum Repr {
+    Id(u64),
+    WithKey { id: u64, key: String },
+}
+
+impl TaskId {
+    /// Placeholder for solutions that were deserialized and have not had
+    /// their id filled in yet
+    pub(crate) const fn unknown() -> Self {
+        Self { id: 0, key: None }
+    }
+
+    /// The same task, created with the key with the given fingerprint
+    pub(crate) const fn with_key(self, key: u64) -> Self {
+        Self {
+            key: Some(key),
+            ..self
+        }
+    }
+
+    /// The fingerprint of the API key the task was created with, if known
+    pub(crate) const fn key(self) -> Option<u64> {
+        self.key
+    }
+}
+
+impl From<u64> for TaskId {
+    fn from(value: u64) -> Self {
+        Self {
+            id: value,
+            key: None,
+        }
+    }
+}
+
+impl From<TaskId> for u64 {
+    fn from(value: TaskId) -> Self {
+        value.id
+    }
+}
+
+impl TryFrom<Repr> for TaskId {
+    type Error = ParseIntError;
+
+    fn try_from(value: Repr) -> Result<Self, Self::Error> {
+        match value {
+            Repr::Id(id) => Ok(id.into()),
+            Repr::WithKey { id, key } => {
+                Ok(Self::from(id).with_key(u64::from_str_radix(&key, 16)?))
+            }
+        }
+    }
+}
+
+impl From<TaskId> for Repr {
+    fn from(value: TaskId) -> Self {
+        value.key.map_or(Self::Id(value.id), |key| Self::WithKey {
+            id: value.id,
+            key: format!("{key:016x}"),
+        })
+    }
+}
+
+impl Display for TaskId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.id.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{from_str, json, to_string, to_value};
+
+    use super::TaskId;
+
+    #[test]
+    fn serialization() {
+        let task_id = TaskId::from(72_345_678_901);
+
+        assert_eq!(to_string(&task_id).unwrap(), "72345678901");
+        assert_eq!(from_str::<TaskId>("72345678901").unwrap(), task_id);
+    }
+
+    #[test]
+    fn serialization_with_key() {
+        let task_id = TaskId::from(72_345_678_901).with_key(0xaf63_dc4c_8601_ec8c);
+        let serialized = json!({ "id": 72_345_678_901_u64, "key": "af63dc4c8601ec8c" });
+
+        assert_eq!(to_value(task_id).unwrap(), serialized);
+        assert_eq!(
+            from_str::<TaskId>(&serialized.to_string()).unwrap(),
+            task_id
+        );
+        assert_eq!(task_id.to_string(), "72345678901");
+        assert!(from_str::<TaskId>(r#"{ "id": 72345678901, "key": "KEY" }"#).is_err());
+    }
+}
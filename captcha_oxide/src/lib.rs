@@ -20,11 +20,11 @@
 //!     .min_score(0.3)
 //!     .build();
 //!   
-//!   let solution = solver
-//!     .solve(args)
-//!     .await?
-//!     .solution
-//!     .g_recaptcha_response;
+//!   let solved = solver.solve(&args).await?;
+//!   # // Only `None` when a callback URL is set without a pingback receiver
+//!   # #[cfg(feature = "callback")]
+//!   # let solved = solved.unwrap();
+//!   let solution = solved.solution.g_recaptcha_response;
 //!   
 //!   assert!(!solution.is_empty());
 //!   
@@ -52,13 +52,23 @@
 pub(crate) const SOFT_ID: u16 = 4143;
 
 pub mod captcha;
-mod captcha_solver;
+pub mod captcha_solver;
 pub mod cookie;
 mod language_pool;
+#[cfg(test)]
+mod mock;
+pub mod money;
 mod prelude;
 pub mod proxy;
+pub mod transport;
 mod two_captcha;
+mod two_captcha_v1;
 
 pub use captcha::Captcha;
 pub use captcha_solver::CaptchaSolver;
+pub use money::Money;
 pub use prelude::{Error, Result};
+
+/// Re-exported for converting to and from [`Money`] without depending on
+/// `rust_decimal`
+pub use rust_decimal::Decimal;
@@ -0,0 +1,85 @@
+//! Helpers for the tests that talk to a [`MockServer`] or replay a fixture
+//! instead of talking to 2captcha
+
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use captcha_oxide_testkit::MockServer;
+
+use crate::{
+    captcha::types::normal_captcha::NormalCaptcha,
+    captcha_solver::{ApiKey, CaptchaSolverBuilder, RetryPolicy, SolveOptions},
+    transport::cassette::Replay,
+    Captcha, CaptchaSolver, Result,
+};
+
+/// The body of the image sent by [`captcha`]
+pub const IMAGE: &str = "R0lGODlhAQABAAAAACw=";
+
+/// An image task, for the tests that do not care about the task itself
+pub fn captcha() -> NormalCaptcha<'static> {
+    NormalCaptcha::builder().body(IMAGE).build()
+}
+
+/// The solution returned by one of the `solve` methods of a solver built by
+/// this module, which the `callback` feature wraps in an [`Option`]. These
+/// solvers have no callback URL, so the solution is always there
+#[cfg(feature = "callback")]
+pub fn solved<T>(solution: Result<Option<T>>) -> Result<T> {
+    solution.map(|solution| solution.expect("The solver should have no callback URL"))
+}
+
+/// The solution returned by one of the `solve` methods of a solver built by
+/// this module, which the `callback` feature wraps in an [`Option`]
+#[cfg(not(feature = "callback"))]
+pub const fn solved<T>(solution: Result<T>) -> Result<T> {
+    solution
+}
+
+/// A solver that sends its requests to `server` and polls for results and
+/// retries without the delays meant for the real API
+pub fn solver(server: &MockServer) -> CaptchaSolver {
+    builder(server).build()
+}
+
+/// Same as [`solver`], for tests that need to change some other setting
+pub fn builder(server: &MockServer) -> CaptchaSolverBuilder<ApiKey> {
+    CaptchaSolver::builder()
+        .api_key("API_KEY")
+        .api_url(server.url())
+        .legacy_api_url(server.url())
+        .retry_policy(RetryPolicy::default().initial_backoff(Duration::from_millis(1)))
+        .solve_options(fast())
+}
+
+/// A solver that replays the cassette `fixtures/{name}.json`, failing if
+/// any request differs from the one in it, along with the [`Replay`] to
+/// check that every interaction was used.
+///
+/// The fixtures are synthetic: they follow the examples of the 2captcha
+/// docs, with placeholder task ids, tokens and costs, and were not
+/// recorded from real solves
+pub fn fixture(name: &str) -> (CaptchaSolver, Arc<Replay>) {
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "fixtures",
+        &format!("{name}.json"),
+    ]
+    .iter()
+    .collect();
+    let replay = Arc::new(Replay::from_file(path).expect("The fixture should exist"));
+
+    let solver = CaptchaSolver::builder()
+        .api_key("API_KEY")
+        .transport(replay.clone())
+        .retry_policy(RetryPolicy::none())
+        .solve_options(fast())
+        .build();
+
+    (solver, replay)
+}
+
+fn fast() -> SolveOptions {
+    SolveOptions::new()
+        .initial_delay(Duration::ZERO)
+        .poll_interval(Duration::from_millis(10))
+}
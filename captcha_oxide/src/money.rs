@@ -0,0 +1,263 @@
+use std::{
+    fmt::{self, Display},
+    iter::Sum,
+    ops::{Add, AddAssign, Mul, Sub, SubAssign},
+    str::FromStr,
+};
+
+use rust_decimal::Decimal;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// An exact amount of money, in the currency of the account, used for the
+/// cost of tasks, the balance and spend limits.
+///
+/// 2captcha sends costs as strings and balances as numbers. Both are
+/// parsed into the same decimal representation without rounding, so sums
+/// of costs match the amounts shown in the dashboard. It serializes as a
+/// string, to keep every digit.
+///
+/// # Example
+/// ```
+/// use captcha_oxide::Money;
+///
+/// let cost: Money = "0.00299".parse()?;
+/// let total: Money = [cost, cost, cost].into_iter().sum();
+///
+/// assert_eq!(total, Money::new(897, 5));
+/// assert_eq!(total.to_string(), "0.00897");
+/// # Ok::<_, captcha_oxide::money::ParseMoneyError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Money(Decimal);
+
+/// The error returned when a string is not a valid amount of money
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Invalid amount of money: {0}")]
+pub struct ParseMoneyError(String);
+
+impl Money {
+    pub const ZERO: Self = Self(Decimal::ZERO);
+
+    /// `units` scaled down by `10^scale`, e.g. `Money::new(299, 5)` is
+    /// `0.00299`
+    ///
+    /// # Panics
+    /// If `scale` is greater than 28
+    #[must_use]
+    pub fn new(units: i64, scale: u32) -> Self {
+        Self(Decimal::new(units, scale))
+    }
+
+    /// The amount as a [`Decimal`], for arithmetic not covered by [`Money`]
+    #[must_use]
+    pub const fn as_decimal(self) -> Decimal {
+        self.0
+    }
+
+    /// Whether the amount is below zero
+    #[must_use]
+    pub const fn is_negative(self) -> bool {
+        self.0.is_sign_negative() && !self.0.is_zero()
+    }
+}
+
+impl From<Decimal> for Money {
+    fn from(value: Decimal) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Money> for Decimal {
+    fn from(value: Money) -> Self {
+        value.0
+    }
+}
+
+impl From<u32> for Money {
+    fn from(value: u32) -> Self {
+        Self(value.into())
+    }
+}
+
+impl FromStr for Money {
+    type Err = ParseMoneyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Decimal::from_str_exact(s.trim())
+            .or_else(|_| Decimal::from_scientific(s.trim()))
+            .map(Self)
+            .map_err(|_| ParseMoneyError(s.to_owned()))
+    }
+}
+
+impl TryFrom<f64> for Money {
+    type Error = ParseMoneyError;
+
+    /// Converts the shortest decimal representation of `value`, so the
+    /// `0.1` in a JSON response is exactly `0.1`
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        value.to_string().parse()
+    }
+}
+
+impl Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.normalize().fmt(f)
+    }
+}
+
+impl Add for Money {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, rhs: Self) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub for Money {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign for Money {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.0 -= rhs.0;
+    }
+}
+
+impl Mul<Decimal> for Money {
+    type Output = Self;
+
+    fn mul(self, rhs: Decimal) -> Self::Output {
+        Self(self.0 * rhs)
+    }
+}
+
+impl Mul<u32> for Money {
+    type Output = Self;
+
+    fn mul(self, rhs: u32) -> Self::Output {
+        Self(self.0 * Decimal::from(rhs))
+    }
+}
+
+impl Sum for Money {
+    fn sum<I>(iter: I) -> Self
+    where
+        I: Iterator<Item = Self>,
+    {
+        iter.fold(Self::ZERO, Add::add)
+    }
+}
+
+impl<'a> Sum<&'a Self> for Money {
+    fn sum<I>(iter: I) -> Self
+    where
+        I: Iterator<Item = &'a Self>,
+    {
+        iter.copied().sum()
+    }
+}
+
+impl Serialize for Money {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+struct Visitor;
+
+impl de::Visitor<'_> for Visitor {
+    type Value = Money;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an amount of money as a string or a number")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        v.parse().map_err(E::custom)
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Money::try_from(v).map_err(E::custom)
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(Money(v.into()))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(Money(v.into()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{from_str, json, to_value};
+
+    use super::Money;
+
+    #[test]
+    fn parses_strings_and_numbers() {
+        assert_eq!(
+            from_str::<Money>(r#""0.00299""#).unwrap(),
+            Money::new(299, 5)
+        );
+        assert_eq!(
+            from_str::<Money>("12.3456").unwrap(),
+            Money::new(123_456, 4)
+        );
+        assert_eq!(from_str::<Money>("0.1").unwrap(), Money::new(1, 1));
+        assert_eq!(from_str::<Money>("3").unwrap(), Money::from(3));
+        assert!(from_str::<Money>(r#""free""#).is_err());
+    }
+
+    #[test]
+    fn arithmetic_is_exact() {
+        let cost = Money::new(1, 1);
+        let total: Money = std::iter::repeat(cost).take(3).sum();
+
+        assert_eq!(total, Money::new(3, 1));
+        assert_eq!(total - cost * 3, Money::ZERO);
+        assert!((Money::ZERO - cost).is_negative());
+    }
+
+    #[test]
+    fn serializes_as_a_string() {
+        assert_eq!(to_value(Money::new(299, 5)).unwrap(), json!("0.00299"));
+        assert_eq!(Money::new(500, 2).to_string(), "5");
+    }
+}
@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use crate::captcha_solver::error::ErrorClass;
+
 pub type Result<T> = core::result::Result<T, Error>;
 
 #[derive(Error, Debug)]
@@ -11,8 +13,105 @@ pub enum Error {
     Serialize(#[from] serde_json::Error),
 
     #[error(transparent)]
-    Http(#[from] rquest::Error),
+    Http(#[from] crate::transport::Error),
 
     #[error(transparent)]
-    TwoCaptchaError(#[from] crate::captcha_solver::error::Error),
+    TwoCaptchaError(#[from] crate::captcha_solver::error::ApiError),
+
+    /// The task was not solved before the deadline. `task_id` is the id of
+    /// the task, if it had been created already. It may still be solved by
+    /// 2captcha later, so its id is kept for polling or reporting
+    #[error("The task was not solved before the deadline")]
+    Timeout {
+        task_id: Option<crate::captcha_solver::TaskId>,
+    },
+
+    /// Solving the task was cancelled. `task_id` is the id of the task that
+    /// was abandoned, if it had been created already
+    #[error("Solving the task was cancelled")]
+    Cancelled {
+        task_id: Option<crate::captcha_solver::TaskId>,
+    },
+
+    /// Every backend of a [`crate::captcha_solver::FailoverSolver`] failed
+    /// recently and is still cooling down
+    #[error("Every backend is cooling down")]
+    NoBackendAvailable,
+
+    /// Every API key of the solver reached its daily spend limit
+    #[error("Every API key reached its daily spend limit")]
+    SpendLimitReached,
+
+    /// The task was created with an API key the solver does not have, so
+    /// it cannot be polled or reported
+    #[error("Task {task_id} was created with an API key the solver does not have")]
+    UnknownApiKey {
+        task_id: crate::captcha_solver::TaskId,
+    },
+
+    /// The spend of the solver reached one of the limits of its
+    /// [`crate::captcha_solver::Budget`], so no more tasks are created
+    /// until the period is over
+    #[error("The budget per {period} is exhausted")]
+    BudgetExceeded {
+        period: crate::captcha_solver::BudgetPeriod,
+    },
+}
+
+impl Error {
+    /// The [`ErrorClass`] this error belongs to. Failures to reach the API or
+    /// to parse its responses, as well as timeouts, are [`ErrorClass::Transient`],
+    /// invalid URLs, cancellations and unknown API keys are
+    /// [`ErrorClass::Request`], having no backend to fail over to is
+    /// [`ErrorClass::Throttled`] and reaching a spend limit is
+    /// [`ErrorClass::Account`]
+    #[must_use]
+    pub const fn class(&self) -> ErrorClass {
+        match self {
+            Self::UrlParse(_) | Self::Cancelled { .. } | Self::UnknownApiKey { .. } => {
+                ErrorClass::Request
+            }
+            Self::Serialize(_) | Self::Timeout { .. } => ErrorClass::Transient,
+            Self::NoBackendAvailable => ErrorClass::Throttled,
+            Self::SpendLimitReached | Self::BudgetExceeded { .. } => ErrorClass::Account,
+            Self::Http(e) => e.class(),
+            Self::TwoCaptchaError(e) => e.class(),
+        }
+    }
+
+    /// Whether the API certainly did not act on the request that failed with
+    /// this error, because the request was never sent, was turned away or
+    /// was answered with an error code
+    pub(crate) const fn is_rejection(&self) -> bool {
+        match self {
+            Self::Http(e) => e.is_rejection(),
+            Self::TwoCaptchaError(_) => true,
+            _ => false,
+        }
+    }
+
+    /// The code of the error returned by the API, e.g. `ERROR_ZERO_BALANCE`.
+    /// Errors that did not come from the API have codes of their own, e.g.
+    /// `HTTP_ERROR` or `TIMEOUT`
+    #[must_use]
+    pub fn code(&self) -> &str {
+        match self {
+            Self::UrlParse(_) => "INVALID_URL",
+            Self::Serialize(_) => "MALFORMED_RESPONSE",
+            Self::Http(_) => "HTTP_ERROR",
+            Self::TwoCaptchaError(e) => e.code(),
+            Self::Timeout { .. } => "TIMEOUT",
+            Self::Cancelled { .. } => "CANCELLED",
+            Self::NoBackendAvailable => "NO_BACKEND_AVAILABLE",
+            Self::SpendLimitReached => "SPEND_LIMIT_REACHED",
+            Self::UnknownApiKey { .. } => "UNKNOWN_API_KEY",
+            Self::BudgetExceeded { .. } => "BUDGET_EXCEEDED",
+        }
+    }
+}
+
+impl From<crate::captcha_solver::error::Error> for Error {
+    fn from(value: crate::captcha_solver::error::Error) -> Self {
+        Self::TwoCaptchaError(value.into())
+    }
 }
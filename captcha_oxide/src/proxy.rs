@@ -1,3 +1,5 @@
+use std::fmt::{self, Debug};
+
 use serde::{Serialize, Serializer};
 
 mod address;
@@ -6,7 +8,9 @@ mod kind;
 pub use address::Address;
 pub use kind::Kind;
 
-#[derive(Serialize, Debug)]
+/// A proxy for the workers to solve a task through. Its password is left
+/// out of the [`Debug`] output, so tasks can be logged safely
+#[derive(Serialize)]
 pub struct Proxy<'a> {
     #[serde(rename = "proxyType")]
     pub kind: Kind,
@@ -24,6 +28,18 @@ pub struct Proxy<'a> {
     pub password: Option<&'a str>,
 }
 
+impl Debug for Proxy<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Proxy")
+            .field("kind", &self.kind)
+            .field("address", &self.address)
+            .field("port", &self.port)
+            .field("login", &self.login)
+            .field("password", &self.password.map(|_| "[redacted]"))
+            .finish()
+    }
+}
+
 /// The 2captcha API expects the proxy's port to be a string, but knowing it should
 /// always be a number, we want [`Proxy::port`] to be of type [`u16`], which means
 /// we need to manually serialize it as a string
@@ -53,5 +69,6 @@ mod test {
         let expected = r#"{"proxyType":"http","proxyAddress":"1.2.3.4","proxyPort":"8080","proxyLogin":"user23","proxyPassword":"p4$$w0rd"}"#;
 
         assert_eq!(to_string(&proxy).unwrap(), expected);
+        assert!(!format!("{proxy:?}").contains("p4$$w0rd"));
     }
 }
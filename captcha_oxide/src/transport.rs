@@ -0,0 +1,216 @@
+//! The HTTP layer used by [`crate::CaptchaSolver`] to talk to the API.
+//!
+//! Every call the solver makes to the API is a `POST` request with a JSON
+//! body, so a [`Transport`] only has to know how to send one of those. The
+//! few methods only available in the legacy `res.php` API are sent as forms
+//! with [`Transport::post_form`]. Implementations
+//! are provided for the clients of the HTTP crates enabled through cargo
+//! features:
+//!
+//! | Feature   | Transport               |
+//! |-----------|-------------------------|
+//! | `reqwest` | [`reqwest::Client`] (rustls, enabled by default) |
+//! | `rquest`  | [`rquest::Client`]      |
+//! | `ureq`    | [`ureq::Agent`] (blocking, run on tokio's blocking pool) |
+//!
+//! When more than one of them is enabled, the default transport is picked in
+//! the order `rquest`, `reqwest`, `ureq`. You can always provide your own
+//! transport with [`crate::captcha_solver::CaptchaSolverBuilder::transport`].
+//!
+//! The [`cassette`] module provides transports that record the exchanges
+//! with the API to a file and replay them later, e.g. in tests
+//!
+//! [`reqwest::Client`]: https://docs.rs/reqwest/latest/reqwest/struct.Client.html
+//! [`rquest::Client`]: https://docs.rs/rquest/latest/rquest/struct.Client.html
+//! [`ureq::Agent`]: https://docs.rs/ureq/2/ureq/struct.Agent.html
+
+use std::{future::Future, pin::Pin, sync::Arc};
+
+use thiserror::Error;
+use url::Url;
+
+use crate::captcha_solver::error::ErrorClass;
+
+pub mod cassette;
+#[cfg(feature = "reqwest")]
+mod reqwest;
+#[cfg(feature = "rquest")]
+mod rquest;
+#[cfg(feature = "ureq")]
+mod ureq;
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Represents a failure to get a response body from the API
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("The API responded with HTTP status {0}")]
+    Status(u16),
+
+    /// No connection to the API could be made, so the request was never
+    /// sent
+    #[error("Could not connect to the API: {0}")]
+    Connect(Box<dyn std::error::Error + Send + Sync>),
+
+    #[error(transparent)]
+    Other(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl Error {
+    /// The [`ErrorClass`] of this error. Rate limiting responses are
+    /// [`ErrorClass::Throttled`], other client errors are [`ErrorClass::Request`]
+    /// and everything else is [`ErrorClass::Transient`]
+    #[must_use]
+    pub const fn class(&self) -> ErrorClass {
+        match self {
+            Self::Status(429) => ErrorClass::Throttled,
+            Self::Status(400..=499) => ErrorClass::Request,
+            Self::Status(_) | Self::Connect(_) | Self::Other(_) => ErrorClass::Transient,
+        }
+    }
+
+    /// Whether the API certainly did not act on the request: it was never
+    /// sent, or it was turned away with a `429` or a `503`
+    pub(crate) const fn is_rejection(&self) -> bool {
+        matches!(self, Self::Connect(_) | Self::Status(429 | 503))
+    }
+}
+
+/// Sends the JSON requests made by [`crate::CaptchaSolver`]
+pub trait Transport: Send + Sync {
+    /// Sends `body` to `url` in a `POST` request with a JSON content type
+    /// and returns the body of the response.
+    ///
+    /// Responses with a non-success status code must be reported as
+    /// [`Error::Status`], and failures to connect to the API as
+    /// [`Error::Connect`]
+    fn post_json<'a>(
+        &'a self,
+        url: &'a Url,
+        body: Vec<u8>,
+    ) -> BoxFuture<'a, Result<Vec<u8>, Error>>;
+
+    /// Sends `body` to `url` in a `POST` request with an
+    /// `application/x-www-form-urlencoded` content type and returns the body
+    /// of the response.
+    ///
+    /// Only the legacy API, e.g. managing pingback addresses, needs this,
+    /// so the default implementation fails with [`Error::Other`]
+    fn post_form<'a>(
+        &'a self,
+        url: &'a Url,
+        body: Vec<u8>,
+    ) -> BoxFuture<'a, Result<Vec<u8>, Error>> {
+        let _ = (url, body);
+
+        Box::pin(async { Err(Error::Other("This transport cannot send forms".into())) })
+    }
+}
+
+impl<T> Transport for Arc<T>
+where
+    T: Transport + ?Sized,
+{
+    fn post_json<'a>(
+        &'a self,
+        url: &'a Url,
+        body: Vec<u8>,
+    ) -> BoxFuture<'a, Result<Vec<u8>, Error>> {
+        (**self).post_json(url, body)
+    }
+
+    fn post_form<'a>(
+        &'a self,
+        url: &'a Url,
+        body: Vec<u8>,
+    ) -> BoxFuture<'a, Result<Vec<u8>, Error>> {
+        (**self).post_form(url, body)
+    }
+}
+
+#[cfg(feature = "rquest")]
+pub(crate) fn default() -> Arc<dyn Transport> {
+    Arc::new(::rquest::Client::new())
+}
+
+#[cfg(all(feature = "reqwest", not(feature = "rquest")))]
+pub(crate) fn default() -> Arc<dyn Transport> {
+    Arc::new(::reqwest::Client::new())
+}
+
+#[cfg(all(feature = "ureq", not(any(feature = "rquest", feature = "reqwest"))))]
+pub(crate) fn default() -> Arc<dyn Transport> {
+    Arc::new(::ureq::Agent::new())
+}
+
+#[cfg(not(any(feature = "rquest", feature = "reqwest", feature = "ureq")))]
+pub(crate) fn default() -> Arc<dyn Transport> {
+    Arc::new(Unconfigured)
+}
+
+/// Used when no HTTP backend feature is enabled and no transport was given
+/// to the builder, so every request fails instead of the build
+#[cfg(not(any(feature = "rquest", feature = "reqwest", feature = "ureq")))]
+struct Unconfigured;
+
+#[cfg(not(any(feature = "rquest", feature = "reqwest", feature = "ureq")))]
+impl Transport for Unconfigured {
+    fn post_json<'a>(&'a self, _: &'a Url, _: Vec<u8>) -> BoxFuture<'a, Result<Vec<u8>, Error>> {
+        Box::pin(async {
+            Err(Error::Other(
+                "No HTTP backend feature is enabled and no transport was provided".into(),
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Mutex;
+
+    use url::Url;
+
+    use super::{BoxFuture, Error, Transport};
+    use crate::{CaptchaSolver, Money};
+
+    #[derive(Default)]
+    struct Canned {
+        requests: Mutex<Vec<(String, String)>>,
+    }
+
+    impl Transport for Canned {
+        fn post_json<'a>(
+            &'a self,
+            url: &'a Url,
+            body: Vec<u8>,
+        ) -> BoxFuture<'a, Result<Vec<u8>, Error>> {
+            self.requests
+                .lock()
+                .unwrap()
+                .push((url.to_string(), String::from_utf8(body).unwrap()));
+
+            Box::pin(async { Ok(br#"{ "errorId": 0, "balance": 1.5 }"#.to_vec()) })
+        }
+    }
+
+    #[tokio::test]
+    async fn custom_transport() {
+        let transport = std::sync::Arc::new(Canned::default());
+        let solver = CaptchaSolver::builder()
+            .api_key("API_KEY")
+            .api_url(Url::parse("http://localhost:8080/2captcha").unwrap())
+            .transport(transport.clone())
+            .build();
+
+        let balance = solver.get_balance().await.unwrap();
+
+        assert_eq!(balance, Money::new(15, 1));
+        assert_eq!(
+            *transport.requests.lock().unwrap(),
+            [(
+                "http://localhost:8080/2captcha/getBalance".to_owned(),
+                r#"{"clientKey":"API_KEY"}"#.to_owned()
+            )]
+        );
+    }
+}
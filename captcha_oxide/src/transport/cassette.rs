@@ -0,0 +1,431 @@
+//! Recording and replaying the requests a [`crate::CaptchaSolver`] makes.
+//!
+//! A cassette is a JSON file holding every request sent to the API and the
+//! response it got, in order. Form requests are stored as a JSON object
+//! holding their fields. The API key and any proxy credentials are
+//! replaced with `"REDACTED"` before they are written, so cassettes can be
+//! committed alongside the tests that replay them.
+//!
+//! # Example
+//! ```no_run
+//! use captcha_oxide::{CaptchaSolver, transport::cassette::Replay};
+//!
+//! // Record the exchanges of a real solve once...
+//! let recorder = CaptchaSolver::builder()
+//!     .api_key("YOUR TWOCAPTCHA API KEY")
+//!     .record_to("cassettes/recaptcha_v2.json")
+//!     .build();
+//!
+//! // ...and replay them in every test run afterwards
+//! let replayer = CaptchaSolver::builder()
+//!     .api_key("ANY KEY")
+//!     .transport(Replay::from_file("cassettes/recaptcha_v2.json")?)
+//!     .build();
+//! # Ok::<_, std::io::Error>(())
+//! ```
+
+use std::{
+    collections::VecDeque,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use url::Url;
+
+use super::{BoxFuture, Error, Transport};
+
+/// The fields replaced with `"REDACTED"`, wherever they appear in a request.
+/// `key` is the API key in form requests
+const SECRETS: [&str; 3] = ["clientKey", "key", "proxyPassword"];
+
+/// The field of form requests holding the proxy as `login:password@host`,
+/// whose credentials are replaced with `"REDACTED"`
+const PROXY: &str = "proxy";
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct Cassette {
+    interactions: Vec<Interaction>,
+}
+
+/// A single request sent to the API and the response it got. Responses
+/// that were not valid JSON are stored as a JSON string
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+struct Interaction {
+    method: String,
+    request: Value,
+    response: Value,
+}
+
+/// A [`Transport`] that sends every request through another transport and
+/// writes the exchange to a cassette file.
+///
+/// The file is rewritten after every response so it is complete even if the
+/// process is killed. Requests that fail in the inner transport are not
+/// recorded.
+///
+/// Use [`crate::captcha_solver::CaptchaSolverBuilder::record_to`] to record
+/// the requests of a solver using its default transport
+pub struct Recorder<T> {
+    inner: T,
+    path: PathBuf,
+    cassette: Mutex<Cassette>,
+}
+
+impl<T> Recorder<T>
+where
+    T: Transport,
+{
+    /// Records the exchanges made through `inner` to the file at `path`,
+    /// replacing it if it exists
+    pub fn new<P>(inner: T, path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self {
+            inner,
+            path: path.into(),
+            cassette: Mutex::default(),
+        }
+    }
+
+    async fn exchange(&self, url: &Url, body: Vec<u8>, form: bool) -> Result<Vec<u8>, Error> {
+        let request = parse_request(&body, form);
+        let response = if form {
+            self.inner.post_form(url, body).await?
+        } else {
+            self.inner.post_json(url, body).await?
+        };
+
+        self.record(Interaction {
+            method: method(url).to_owned(),
+            request,
+            response: parse(&response),
+        })
+        .map_err(|e| Error::Other(Box::new(e)))?;
+
+        Ok(response)
+    }
+
+    /// Adds `interaction` to the cassette and rewrites the file. The lock is
+    /// held while writing so a slow write cannot overwrite a newer cassette
+    fn record(&self, interaction: Interaction) -> io::Result<()> {
+        let mut cassette = self.cassette.lock().unwrap_or_else(PoisonError::into_inner);
+        cassette.interactions.push(interaction);
+
+        fs::write(&self.path, serde_json::to_vec_pretty(&*cassette)?)
+    }
+}
+
+impl<T> Transport for Recorder<T>
+where
+    T: Transport,
+{
+    fn post_json<'a>(
+        &'a self,
+        url: &'a Url,
+        body: Vec<u8>,
+    ) -> BoxFuture<'a, Result<Vec<u8>, Error>> {
+        Box::pin(self.exchange(url, body, false))
+    }
+
+    fn post_form<'a>(
+        &'a self,
+        url: &'a Url,
+        body: Vec<u8>,
+    ) -> BoxFuture<'a, Result<Vec<u8>, Error>> {
+        Box::pin(self.exchange(url, body, true))
+    }
+}
+
+/// A [`Transport`] that answers with the responses stored in a cassette,
+/// in the order they were recorded, without sending anything.
+///
+/// Every request is checked against the recorded one, ignoring the redacted
+/// fields, so a change in how a task is serialized fails the replay with an
+/// [`Error::Other`] describing the difference
+pub struct Replay {
+    interactions: Mutex<VecDeque<Interaction>>,
+}
+
+impl Replay {
+    /// Loads the cassette at `path`
+    ///
+    /// # Errors
+    /// This function will error if the file cannot be read or is not a
+    /// valid cassette
+    pub fn from_file<P>(path: P) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        Ok(Self::from_json(&fs::read_to_string(path)?)?)
+    }
+
+    /// Loads a cassette from its contents
+    ///
+    /// # Errors
+    /// This function will error if `json` is not a valid cassette
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        let cassette: Cassette = serde_json::from_str(json)?;
+
+        Ok(Self {
+            interactions: Mutex::new(cassette.interactions.into()),
+        })
+    }
+
+    /// Whether every recorded interaction has been replayed
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.interactions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_empty()
+    }
+
+    fn next(&self, url: &Url, body: &[u8], form: bool) -> Result<Vec<u8>, Error> {
+        let method = method(url);
+        let request = parse_request(body, form);
+
+        let interaction = self
+            .interactions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .pop_front()
+            .ok_or_else(|| {
+                Error::Other(format!("The cassette has no response left for {method}").into())
+            })?;
+
+        if interaction.method != method || interaction.request != request {
+            return Err(Error::Other(
+                format!(
+                    "Expected {} with {}, got {method} with {request}",
+                    interaction.method, interaction.request
+                )
+                .into(),
+            ));
+        }
+
+        Ok(match interaction.response {
+            Value::String(raw) => raw.into_bytes(),
+            response => response.to_string().into_bytes(),
+        })
+    }
+}
+
+impl Transport for Replay {
+    fn post_json<'a>(
+        &'a self,
+        url: &'a Url,
+        body: Vec<u8>,
+    ) -> BoxFuture<'a, Result<Vec<u8>, Error>> {
+        let response = self.next(url, &body, false);
+
+        Box::pin(async move { response })
+    }
+
+    fn post_form<'a>(
+        &'a self,
+        url: &'a Url,
+        body: Vec<u8>,
+    ) -> BoxFuture<'a, Result<Vec<u8>, Error>> {
+        let response = self.next(url, &body, true);
+
+        Box::pin(async move { response })
+    }
+}
+
+/// The name of the API method `url` points to, e.g. `createTask`
+fn method(url: &Url) -> &str {
+    url.path_segments()
+        .and_then(Iterator::last)
+        .unwrap_or_default()
+}
+
+/// Parses the body of a request, redacting its secrets. Forms become an
+/// object holding their fields
+fn parse_request(body: &[u8], form: bool) -> Value {
+    if !form {
+        return parse(body);
+    }
+
+    let mut value = Value::Object(
+        url::form_urlencoded::parse(body)
+            .map(|(key, value)| (key.into_owned(), Value::String(value.into_owned())))
+            .collect(),
+    );
+
+    redact(&mut value);
+    value
+}
+
+/// Parses a request or response body, redacting its secrets. Bodies that
+/// are not valid JSON are kept as a string
+fn parse(body: &[u8]) -> Value {
+    let mut value = serde_json::from_slice(body)
+        .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(body).into_owned()));
+
+    redact(&mut value);
+    value
+}
+
+fn redact(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                if SECRETS.contains(&key.as_str()) {
+                    *value = Value::String("REDACTED".to_owned());
+                } else if key == PROXY {
+                    redact_proxy(value);
+                } else {
+                    redact(value);
+                }
+            }
+        }
+        Value::Array(array) => array.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+/// Replaces the credentials of a `login:password@host` proxy
+fn redact_proxy(value: &mut Value) {
+    let Value::String(proxy) = value else {
+        return;
+    };
+
+    if let Some((_, host)) = proxy.rsplit_once('@') {
+        *proxy = format!("REDACTED@{host}");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        env,
+        net::{IpAddr, Ipv4Addr},
+    };
+
+    use captcha_oxide_testkit::{MockServer, TaskScript};
+    use serde_json::json;
+
+    use super::Replay;
+    use url::Url;
+
+    use crate::{
+        captcha::{types::recaptcha::v2::RecaptchaV2, Captcha},
+        captcha_solver::{Protocol, RetryPolicy},
+        mock,
+        proxy::{Address, Kind, Proxy},
+        transport, CaptchaSolver,
+    };
+
+    #[tokio::test]
+    async fn record_and_replay() {
+        let path = env::temp_dir().join(format!("captcha_oxide_{}.json", std::process::id()));
+        let captcha = mock::captcha();
+
+        let server = MockServer::start().await;
+        server.enqueue(
+            TaskScript::new()
+                .processing(1)
+                .ready(json!({ "text": "w68hp" })),
+        );
+
+        let recording = mock::builder(&server).record_to(&path).build();
+        let original = mock::solved(recording.solve(&captcha).await).unwrap();
+
+        let cassette = std::fs::read_to_string(&path).unwrap();
+        assert!(!cassette.contains("API_KEY"));
+        assert!(cassette.contains(r#""clientKey": "REDACTED""#));
+
+        let replay = std::sync::Arc::new(Replay::from_file(&path).unwrap());
+        let replaying = mock::builder(&server).transport(replay.clone()).build();
+        let replayed = mock::solved(replaying.solve(&captcha).await).unwrap();
+
+        assert_eq!(replayed.solution, original.solution);
+        assert!(replay.is_finished());
+        assert_eq!(server.requests().len(), 3);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn redacts_v1_proxy_credentials() {
+        let path = env::temp_dir().join(format!("captcha_oxide_v1_{}.json", std::process::id()));
+        let server = MockServer::start().await;
+        server.enqueue(TaskScript::new());
+
+        let captcha = <RecaptchaV2>::builder()
+            .website_url(Url::parse("https://example.com").unwrap())
+            .website_key("SITE_KEY")
+            .proxy(Proxy {
+                kind: Kind::Http,
+                address: Address::IpAddress(IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4))),
+                port: 8080,
+                login: Some("user23"),
+                password: Some("p4$$w0rd"),
+            })
+            .build();
+
+        let recording = mock::builder(&server)
+            .protocol(Protocol::V1)
+            .record_to(&path)
+            .build();
+        let _ = recording.submit(&captcha).await.unwrap();
+
+        let cassette = std::fs::read_to_string(&path).unwrap();
+        assert!(!cassette.contains("p4$$w0rd"));
+        assert!(!cassette.contains("user23"));
+        assert!(cassette.contains(r#""proxy": "REDACTED@1.2.3.4:8080""#));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn replay_checks_requests() {
+        let replay = Replay::from_json(
+            r#"{ "interactions": [{
+                "method": "getBalance",
+                "request": { "clientKey": "REDACTED", "extra": true },
+                "response": { "errorId": 0, "balance": 1.5 }
+            }] }"#,
+        )
+        .unwrap();
+
+        let solver = CaptchaSolver::builder()
+            .api_key("API_KEY")
+            .transport(replay)
+            .retry_policy(RetryPolicy::none())
+            .build();
+
+        let error = solver.get_balance().await.unwrap_err();
+
+        assert!(matches!(
+            error,
+            crate::Error::Http(transport::Error::Other(_))
+        ));
+    }
+
+    #[test]
+    fn redacts_form_keys() {
+        let value = super::parse_request(b"key=KEY&action=get_pingback&json=1", true);
+
+        assert_eq!(
+            value,
+            json!({ "key": "REDACTED", "action": "get_pingback", "json": "1" })
+        );
+    }
+
+    #[test]
+    fn redacts_proxy_passwords() {
+        let value = super::parse(
+            br#"{ "clientKey": "KEY", "task": { "proxyLogin": "user", "proxyPassword": "hunter2" } }"#,
+        );
+
+        assert_eq!(
+            value,
+            json!({ "clientKey": "REDACTED", "task": { "proxyLogin": "user", "proxyPassword": "REDACTED" } })
+        );
+    }
+}
@@ -0,0 +1,53 @@
+use ::reqwest::{header::CONTENT_TYPE, Client};
+use url::Url;
+
+use super::{BoxFuture, Error, Transport};
+
+impl Transport for Client {
+    fn post_json<'a>(
+        &'a self,
+        url: &'a Url,
+        body: Vec<u8>,
+    ) -> BoxFuture<'a, Result<Vec<u8>, Error>> {
+        Box::pin(post(self, url, "application/json", body))
+    }
+
+    fn post_form<'a>(
+        &'a self,
+        url: &'a Url,
+        body: Vec<u8>,
+    ) -> BoxFuture<'a, Result<Vec<u8>, Error>> {
+        Box::pin(post(self, url, "application/x-www-form-urlencoded", body))
+    }
+}
+
+async fn post(
+    client: &Client,
+    url: &Url,
+    content_type: &'static str,
+    body: Vec<u8>,
+) -> Result<Vec<u8>, Error> {
+    let response = client
+        .post(url.clone())
+        .header(CONTENT_TYPE, content_type)
+        .body(body)
+        .send()
+        .await?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(Error::Status(status.as_u16()));
+    }
+
+    Ok(response.bytes().await?.to_vec())
+}
+
+impl From<::reqwest::Error> for Error {
+    fn from(value: ::reqwest::Error) -> Self {
+        if value.is_connect() {
+            Self::Connect(Box::new(value))
+        } else {
+            Self::Other(Box::new(value))
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use std::io::Read;
+
+use ::ureq::{Agent, ErrorKind};
+use url::Url;
+
+use super::{BoxFuture, Error, Transport};
+
+/// `ureq` is blocking, so requests are moved to tokio's blocking thread pool
+/// to avoid stalling the runtime
+impl Transport for Agent {
+    fn post_json<'a>(
+        &'a self,
+        url: &'a Url,
+        body: Vec<u8>,
+    ) -> BoxFuture<'a, Result<Vec<u8>, Error>> {
+        Box::pin(post(self.clone(), url.clone(), "application/json", body))
+    }
+
+    fn post_form<'a>(
+        &'a self,
+        url: &'a Url,
+        body: Vec<u8>,
+    ) -> BoxFuture<'a, Result<Vec<u8>, Error>> {
+        Box::pin(post(
+            self.clone(),
+            url.clone(),
+            "application/x-www-form-urlencoded",
+            body,
+        ))
+    }
+}
+
+async fn post(
+    agent: Agent,
+    url: Url,
+    content_type: &'static str,
+    body: Vec<u8>,
+) -> Result<Vec<u8>, Error> {
+    tokio::task::spawn_blocking(move || {
+        let response = agent
+            .post(url.as_str())
+            .set("Content-Type", content_type)
+            .send_bytes(&body)?;
+
+        let mut bytes = Vec::new();
+        response
+            .into_reader()
+            .read_to_end(&mut bytes)
+            .map_err(|e| Error::Other(Box::new(e)))?;
+
+        Ok(bytes)
+    })
+    .await
+    .map_err(|e| Error::Other(Box::new(e)))?
+}
+
+impl From<::ureq::Error> for Error {
+    fn from(value: ::ureq::Error) -> Self {
+        match value {
+            ::ureq::Error::Status(status, _) => Self::Status(status),
+            ::ureq::Error::Transport(e)
+                if matches!(e.kind(), ErrorKind::Dns | ErrorKind::ConnectionFailed) =>
+            {
+                Self::Connect(Box::new(e))
+            }
+            e @ ::ureq::Error::Transport(_) => Self::Other(Box::new(e)),
+        }
+    }
+}
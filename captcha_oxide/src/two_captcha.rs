@@ -1,4 +1,5 @@
 pub mod create_task;
 pub mod get_balance;
 pub mod get_task_result;
+pub mod pingback_addresses;
 pub mod report;
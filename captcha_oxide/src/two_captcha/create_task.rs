@@ -1,21 +1,24 @@
 use std::borrow::Cow;
 
-use serde::{Deserialize, Serialize};
+use serde::{ser::SerializeMap, Deserialize, Serialize, Serializer};
 
 #[cfg(feature = "callback")]
 use url::Url;
 
-use crate::{captcha::Captcha, language_pool::LanguagePool};
+use crate::language_pool::LanguagePool;
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Request<'a, T>
 where
-    T: Captcha,
+    T: Serialize,
 {
     pub client_key: &'a str,
     pub task: &'a T,
-    pub soft_id: u16,
+
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub soft_id: Option<SoftId<'a>>,
+
     pub language_pool: LanguagePool,
 
     #[cfg(feature = "callback")]
@@ -23,11 +26,35 @@ where
     pub callback_url: Option<&'a Url>,
 }
 
+/// The id of the software that created the task, sent in a field whose
+/// name depends on the provider
+#[derive(Debug, Clone, Copy)]
+pub struct SoftId<'a> {
+    pub field: &'a str,
+    pub id: u16,
+}
+
+impl Serialize for SoftId<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry(self.field, &self.id)?;
+        map.end()
+    }
+}
+
 #[derive(Deserialize, PartialEq, Eq, Debug)]
 #[serde(untagged, rename_all_fields = "camelCase")]
 pub enum Response<'a> {
-    TaskCreated { task_id: u64 },
-    Error { error_code: Cow<'a, str> },
+    TaskCreated {
+        task_id: u64,
+    },
+    Error {
+        error_id: u16,
+        error_code: Cow<'a, str>,
+
+        #[serde(default)]
+        error_description: Option<Cow<'a, str>>,
+    },
 }
 
 #[cfg(test)]
@@ -37,7 +64,7 @@ mod test {
     use crate::{
         captcha::Captcha,
         language_pool::LanguagePool,
-        two_captcha::create_task::{Request, Response},
+        two_captcha::create_task::{Request, Response, SoftId},
     };
 
     use serde_json::{from_str, to_string};
@@ -49,6 +76,10 @@ mod test {
         fn get_timeout(&self) -> std::time::Duration {
             unimplemented!()
         }
+
+        fn get_task_type(&self) -> &'static str {
+            unimplemented!()
+        }
     }
 
     #[test]
@@ -57,7 +88,10 @@ mod test {
         let request = Request {
             client_key: "API_KEY",
             task: &task,
-            soft_id: 4143,
+            soft_id: Some(SoftId {
+                field: "softId",
+                id: 4143,
+            }),
             language_pool: LanguagePool::En,
 
             #[cfg(feature = "callback")]
@@ -72,13 +106,19 @@ mod test {
     #[test]
     fn response_deserialization() {
         let success = r#"{ "errorId": 0, "taskId": 72345678901 }"#;
-        let error = r#"{ "errorId": 10, "errorCode": "ERROR_ZERO_BALANCE" }"#;
+        let error = r#"{
+            "errorId": 10,
+            "errorCode": "ERROR_ZERO_BALANCE",
+            "errorDescription": "You don't have funds on your account"
+        }"#;
 
         let success_expected = Response::TaskCreated {
             task_id: 72_345_678_901,
         };
         let error_expected = Response::Error {
+            error_id: 10,
             error_code: Cow::Borrowed("ERROR_ZERO_BALANCE"),
+            error_description: Some(Cow::Borrowed("You don't have funds on your account")),
         };
 
         assert_eq!(from_str::<Response<'_>>(success).unwrap(), success_expected);
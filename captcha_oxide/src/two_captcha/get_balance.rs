@@ -2,17 +2,27 @@ use std::borrow::Cow;
 
 use serde::{Deserialize, Serialize};
 
+use crate::Money;
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Request<'a> {
     pub client_key: &'a str,
 }
 
-#[derive(Debug, Deserialize, PartialEq)]
+#[derive(Debug, Deserialize, PartialEq, Eq)]
 #[serde(untagged, rename_all_fields = "camelCase")]
 pub enum Response<'a> {
-    Success { balance: f64 },
-    Error { error_code: Cow<'a, str> },
+    Success {
+        balance: Money,
+    },
+    Error {
+        error_id: u16,
+        error_code: Cow<'a, str>,
+
+        #[serde(default)]
+        error_description: Option<Cow<'a, str>>,
+    },
 }
 
 #[cfg(test)]
@@ -21,7 +31,10 @@ mod test {
 
     use serde_json::{from_str, to_string};
 
-    use crate::two_captcha::get_balance::{Request, Response};
+    use crate::{
+        two_captcha::get_balance::{Request, Response},
+        Money,
+    };
 
     #[test]
     fn request_serialization() {
@@ -43,9 +56,15 @@ mod test {
             "errorDescription": "The request is sent from the IP that is not on the list of your trusted IPs"
         }"#;
 
-        let expected = Response::Success { balance: 0.93958 };
+        let expected = Response::Success {
+            balance: Money::new(93_958, 5),
+        };
         let error_expected = Response::Error {
+            error_id: 11,
             error_code: Cow::Borrowed("ERROR_IP_NOT_ALLOWED"),
+            error_description: Some(Cow::Borrowed(
+                "The request is sent from the IP that is not on the list of your trusted IPs",
+            )),
         };
 
         assert_eq!(from_str::<Response<'_>>(response).unwrap(), expected);
@@ -27,22 +27,26 @@ where
 
     #[serde(untagged)]
     Error {
-        error_id: u8,
+        error_id: u16,
         error_code: Cow<'a, str>,
-        error_description: Cow<'a, str>,
+
+        #[serde(default)]
+        error_description: Option<Cow<'a, str>>,
     },
 }
 
 #[cfg(test)]
 mod test {
-    use std::{borrow::Cow, collections::HashMap, net::IpAddr, str::FromStr};
+    use std::{borrow::Cow, collections::HashMap, marker::PhantomData, net::IpAddr, str::FromStr};
 
     use chrono::{DateTime, Utc};
     use serde_json::{from_str, to_string};
 
     use crate::{
         captcha::{solution::Solution, Captcha},
+        captcha_solver::{SolveMetrics, TaskId},
         two_captcha::get_task_result::{Request, Response},
+        Money,
     };
 
     impl Captcha for () {
@@ -52,6 +56,10 @@ mod test {
         fn get_timeout(&self) -> std::time::Duration {
             unimplemented!()
         }
+
+        fn get_task_type(&self) -> &'static str {
+            unimplemented!()
+        }
     }
 
     #[test]
@@ -87,18 +95,20 @@ mod test {
 
         let processing_expected = Response::Processing;
         let ready_expected = Response::Ready(Solution {
-            task_id: 0,
+            task_id: TaskId::from(0),
             solution: HashMap::default(),
-            cost: Cow::Borrowed("0.00299"),
+            cost: Some(Money::new(299, 5)),
             create_time: DateTime::<Utc>::from_timestamp_millis(1_692_863_536_000).unwrap(),
             end_time: DateTime::<Utc>::from_timestamp_millis(1_692_863_556_000).unwrap(),
             solve_count: 1,
             ip: IpAddr::from_str("1.2.3.4").unwrap(),
+            metrics: SolveMetrics::default(),
+            lifetime: PhantomData,
         });
         let error_expected = Response::Error {
             error_id: 12,
             error_code: Cow::Borrowed("ERROR_CAPTCHA_UNSOLVABLE"),
-            error_description: Cow::Borrowed("Workers could not solve the Captcha"),
+            error_description: Some(Cow::Borrowed("Workers could not solve the Captcha")),
         };
 
         assert_eq!(
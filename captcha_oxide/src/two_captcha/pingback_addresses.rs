@@ -0,0 +1,116 @@
+//! The `res.php` actions of the legacy API that manage the addresses
+//! 2captcha is allowed to send pingbacks to
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use serde_repr::Deserialize_repr;
+
+#[derive(Serialize, Debug)]
+pub struct Request<'a> {
+    pub key: &'a str,
+    pub action: Action,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub addr: Option<&'a str>,
+
+    /// Asks for a JSON response instead of plain text
+    pub json: u8,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    #[serde(rename = "add_pingback")]
+    Add,
+
+    #[serde(rename = "get_pingback")]
+    Get,
+
+    #[serde(rename = "del_pingback")]
+    Delete,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+pub struct Response<'a> {
+    pub status: Status,
+    pub request: Payload<'a>,
+
+    #[serde(default)]
+    pub error_text: Option<Cow<'a, str>>,
+}
+
+#[derive(Deserialize_repr, Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Status {
+    Error = 0,
+    Success = 1,
+}
+
+/// `OK` or an error code for most actions, and the registered addresses
+/// for `get_pingback`
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum Payload<'a> {
+    Text(Cow<'a, str>),
+    Addresses(Vec<Cow<'a, str>>),
+}
+
+#[cfg(test)]
+mod test {
+    use std::borrow::Cow;
+
+    use serde_json::{from_str, json, to_value};
+
+    use crate::two_captcha::pingback_addresses::{Action, Payload, Request, Response, Status};
+
+    #[test]
+    fn request_serialization() {
+        let request = Request {
+            key: "API_KEY",
+            action: Action::Add,
+            addr: Some("https://example.com/pingback"),
+            json: 1,
+        };
+
+        let expected = json!({
+            "key": "API_KEY",
+            "action": "add_pingback",
+            "addr": "https://example.com/pingback",
+            "json": 1,
+        });
+
+        assert_eq!(to_value(&request).unwrap(), expected);
+    }
+
+    #[test]
+    fn response_deserialization() {
+        let added = r#"{ "status": 1, "request": "OK" }"#;
+        let listed = r#"{ "status": 1, "request": ["https://example.com/pingback"] }"#;
+        let error = r#"{
+            "status": 0,
+            "request": "ERROR_WRONG_USER_KEY",
+            "error_text": "The key is invalid"
+        }"#;
+
+        assert_eq!(
+            from_str::<Response<'_>>(added).unwrap(),
+            Response {
+                status: Status::Success,
+                request: Payload::Text(Cow::Borrowed("OK")),
+                error_text: None,
+            }
+        );
+        assert_eq!(
+            from_str::<Response<'_>>(listed).unwrap().request,
+            Payload::Addresses(vec![Cow::Borrowed("https://example.com/pingback")])
+        );
+        assert_eq!(
+            from_str::<Response<'_>>(error).unwrap(),
+            Response {
+                status: Status::Error,
+                request: Payload::Text(Cow::Borrowed("ERROR_WRONG_USER_KEY")),
+                error_text: Some(Cow::Borrowed("The key is invalid")),
+            }
+        );
+    }
+}
@@ -20,7 +20,11 @@ pub enum Response<'a> {
 
     #[serde(untagged)]
     Error {
+        error_id: u16,
         error_code: Cow<'a, str>,
+
+        #[serde(default)]
+        error_description: Option<Cow<'a, str>>,
     },
 }
 
@@ -55,7 +59,11 @@ mod test {
 
         let expected = Response::Success;
         let error_expected = Response::Error {
+            error_id: 16,
             error_code: Cow::Borrowed("ERROR_NO_SUCH_CAPCHA_ID"),
+            error_description: Some(Cow::Borrowed(
+                "You've provided incorrect captcha ID in the request",
+            )),
         };
 
         assert_eq!(from_str::<Response<'_>>(response).unwrap(), expected);
@@ -0,0 +1,18 @@
+//! The legacy `in.php`/`res.php` API, which takes forms instead of JSON.
+//!
+//! Tasks are translated from the JSON sent to `createTask` into the
+//! parameters `in.php` expects, and results from `res.php` are translated
+//! back into `getTaskResult` responses, so the rest of the crate only ever
+//! deals with the JSON API
+
+use serde_repr::Deserialize_repr;
+
+pub mod res;
+pub mod submit;
+
+#[derive(Deserialize_repr, Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Status {
+    Error = 0,
+    Success = 1,
+}
@@ -0,0 +1,211 @@
+//! The `res.php` actions that read the results of tasks created through
+//! `in.php`, report them and read the balance
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use super::Status;
+
+/// The code `res.php` answers with while the task is being processed
+pub const NOT_READY: &str = "CAPCHA_NOT_READY";
+
+#[derive(Serialize, Debug)]
+pub struct Request<'a> {
+    pub key: &'a str,
+    pub action: Action,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<u64>,
+
+    /// Asks for a JSON response instead of plain text
+    pub json: u8,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    /// Same as `get`, but also returns the price of the task
+    Get2,
+    GetBalance,
+    ReportGood,
+    ReportBad,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+pub struct Response<'a> {
+    pub status: Status,
+
+    /// The answer, or an error code
+    pub request: Value,
+
+    #[serde(default)]
+    pub price: Option<Cow<'a, str>>,
+
+    #[serde(default)]
+    pub useragent: Option<Cow<'a, str>>,
+
+    #[serde(default, rename = "respKey")]
+    pub resp_key: Option<Cow<'a, str>>,
+
+    #[serde(default)]
+    pub error_text: Option<Cow<'a, str>>,
+}
+
+impl Response<'_> {
+    /// Builds the body `getTaskResult` would have answered with once the
+    /// task is solved.
+    ///
+    /// `res.php` only returns the answer and its price, so the IP is left
+    /// unspecified and the task is said to be created and solved at `now`.
+    /// Answers made of a single string, such as tokens, are set as every
+    /// field a single string solution can have, e.g. `text` and `token`
+    pub fn into_task_result(self, now: u64) -> Value {
+        let mut solution = match self.request {
+            Value::String(answer) => {
+                let mut solution = Map::new();
+                for field in ["text", "token", "gRecaptchaResponse"] {
+                    solution.insert(field.into(), answer.clone().into());
+                }
+                solution
+            }
+            Value::Object(fields) => fields
+                .into_iter()
+                .map(|(name, value)| (camel_case(&name), value))
+                .collect(),
+            answer => return task_result(&answer, self.price, now),
+        };
+
+        if let Some(user_agent) = self.useragent {
+            solution.insert("userAgent".into(), user_agent.into());
+        }
+        if let Some(resp_key) = self.resp_key {
+            solution.insert("respKey".into(), resp_key.into());
+        }
+
+        task_result(&Value::Object(solution), self.price, now)
+    }
+}
+
+/// The cost is left out when `res.php` does not return the price, rather
+/// than reported as zero, so the task is not counted towards any spend
+fn task_result(solution: &Value, price: Option<Cow<'_, str>>, now: u64) -> Value {
+    let mut result = json!({
+        "errorId": 0,
+        "status": "ready",
+        "solution": solution,
+        "ip": "0.0.0.0",
+        "createTime": now,
+        "endTime": now,
+        "solveCount": 1,
+    });
+
+    if let Some(price) = price {
+        result["cost"] = price.into();
+    }
+
+    result
+}
+
+/// Converts the `snake_case` fields of answers such as GeeTest's, which
+/// are prefixed with `geetest_` in v3, to the names `getTaskResult` uses
+fn camel_case(name: &str) -> String {
+    let name = name.strip_prefix("geetest_").unwrap_or(name);
+    let mut words = name.split('_');
+
+    words.next().unwrap_or_default().to_owned()
+        + &words
+            .map(|word| {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                    .unwrap_or_default()
+            })
+            .collect::<String>()
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{from_str, json, to_value};
+
+    use super::{Action, Request, Response, Status};
+
+    #[test]
+    fn request_serialization() {
+        let request = Request {
+            key: "API_KEY",
+            action: Action::Get2,
+            id: Some(2_122_988_149),
+            json: 1,
+        };
+
+        let expected = json!({
+            "key": "API_KEY",
+            "action": "get2",
+            "id": 2_122_988_149_u64,
+            "json": 1,
+        });
+
+        assert_eq!(to_value(&request).unwrap(), expected);
+    }
+
+    #[test]
+    fn response_deserialization() {
+        let ready = r#"{ "status": 1, "request": "w68hp", "price": "0.00099" }"#;
+        let not_ready = r#"{ "status": 0, "request": "CAPCHA_NOT_READY" }"#;
+
+        let ready = from_str::<Response<'_>>(ready).unwrap();
+
+        assert_eq!(ready.status, Status::Success);
+        assert_eq!(ready.request, json!("w68hp"));
+        assert_eq!(ready.price.as_deref(), Some("0.00099"));
+        assert_eq!(
+            from_str::<Response<'_>>(not_ready).unwrap().status,
+            Status::Error
+        );
+    }
+
+    #[test]
+    fn translates_answers() {
+        let token: Response<'_> = from_str(
+            r#"{ "status": 1, "request": "P1_eyJ0eXAi", "useragent": "Mozilla/5.0", "respKey": "E0_eyJ0eXAi" }"#,
+        )
+        .unwrap();
+        let geetest: Response<'_> = from_str(
+            r#"{
+                "status": 1,
+                "request": {
+                    "geetest_challenge": "1ad03db8",
+                    "geetest_validate": "ad6b0c7a",
+                    "geetest_seccode": "ad6b0c7a|jordan"
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let token = token.into_task_result(1_692_863_536);
+        let geetest = geetest.into_task_result(1_692_863_536);
+
+        assert_eq!(
+            token["solution"],
+            json!({
+                "text": "P1_eyJ0eXAi",
+                "token": "P1_eyJ0eXAi",
+                "gRecaptchaResponse": "P1_eyJ0eXAi",
+                "userAgent": "Mozilla/5.0",
+                "respKey": "E0_eyJ0eXAi",
+            })
+        );
+        assert!(token.get("cost").is_none());
+        assert_eq!(
+            geetest["solution"],
+            json!({
+                "challenge": "1ad03db8",
+                "validate": "ad6b0c7a",
+                "seccode": "ad6b0c7a|jordan",
+            })
+        );
+    }
+}
@@ -0,0 +1,368 @@
+//! `in.php`, which creates a task from the parameters of a form
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+#[cfg(feature = "callback")]
+use url::Url;
+
+use super::Status;
+
+#[derive(Serialize, Debug)]
+pub struct Request<'a> {
+    pub key: &'a str,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub soft_id: Option<u16>,
+
+    /// Asks for a JSON response instead of plain text
+    pub json: u8,
+
+    #[cfg(feature = "callback")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pingback: Option<&'a Url>,
+
+    /// The `method` and parameters of the task, see [`params`]
+    #[serde(flatten)]
+    pub params: Map<String, Value>,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+pub struct Response<'a> {
+    pub status: Status,
+
+    /// The id of the task, or an error code
+    pub request: Cow<'a, str>,
+
+    #[serde(default)]
+    pub error_text: Option<Cow<'a, str>>,
+}
+
+/// How a `createTask` task type is sent to `in.php`
+struct Method {
+    /// The value of the `method` parameter
+    name: &'static str,
+
+    /// Parameters whose name differs from the field of the task
+    renames: &'static [(&'static str, &'static str)],
+
+    /// Parameters that are always sent along with the task
+    extra: &'static [(&'static str, &'static str)],
+}
+
+/// The `in.php` method for each supported task type, without the
+/// `Proxyless` suffix
+const METHODS: &[(&str, Method)] = &[
+    (
+        "ImageToTextTask",
+        Method {
+            name: "base64",
+            renames: &[
+                ("case", "regsense"),
+                ("math", "calc"),
+                ("minLength", "min_len"),
+                ("maxLength", "max_len"),
+                ("comment", "textinstructions"),
+                ("imgInstructions", "imginstructions"),
+            ],
+            extra: &[],
+        },
+    ),
+    (
+        "TextCaptchaTask",
+        Method {
+            name: "text",
+            renames: &[("comment", "textcaptcha")],
+            extra: &[],
+        },
+    ),
+    (
+        "RecaptchaV2Task",
+        Method {
+            name: "userrecaptcha",
+            renames: &[
+                ("websiteKey", "googlekey"),
+                ("recaptchaDataSValue", "data-s"),
+                ("isInvisible", "invisible"),
+                ("apiDomain", "domain"),
+            ],
+            extra: &[],
+        },
+    ),
+    (
+        "RecaptchaV2EnterpriseTask",
+        Method {
+            name: "userrecaptcha",
+            renames: &[
+                ("websiteKey", "googlekey"),
+                ("isInvisible", "invisible"),
+                ("apiDomain", "domain"),
+            ],
+            extra: &[("enterprise", "1")],
+        },
+    ),
+    (
+        "RecaptchaV3Task",
+        Method {
+            name: "userrecaptcha",
+            renames: &[
+                ("websiteKey", "googlekey"),
+                ("minScore", "min_score"),
+                ("pageAction", "action"),
+                ("isEnterprise", "enterprise"),
+                ("apiDomain", "domain"),
+            ],
+            extra: &[("version", "v3")],
+        },
+    ),
+    (
+        "HCaptchaTask",
+        Method {
+            name: "hcaptcha",
+            renames: &[("websiteKey", "sitekey"), ("isInvisible", "invisible")],
+            extra: &[],
+        },
+    ),
+    (
+        "TurnstileTask",
+        Method {
+            name: "turnstile",
+            renames: &[("websiteKey", "sitekey")],
+            extra: &[],
+        },
+    ),
+    (
+        "FunCaptchaTask",
+        Method {
+            name: "funcaptcha",
+            renames: &[
+                ("websitePublicKey", "publickey"),
+                ("funcaptchaApiJssubdomain", "surl"),
+            ],
+            extra: &[],
+        },
+    ),
+    (
+        "GeeTestTask",
+        Method {
+            name: "geetest",
+            renames: &[("geetestApiServerSubdomain", "api_server")],
+            extra: &[],
+        },
+    ),
+    (
+        "AmazonTask",
+        Method {
+            name: "amazon_waf",
+            renames: &[
+                ("websiteKey", "sitekey"),
+                ("challengeScript", "challenge_script"),
+                ("captchaScript", "captcha_script"),
+            ],
+            extra: &[],
+        },
+    ),
+    (
+        "CapyTask",
+        Method {
+            name: "capy",
+            renames: &[("websiteKey", "captchakey")],
+            extra: &[],
+        },
+    ),
+    (
+        "MtCaptchaTask",
+        Method {
+            name: "mt_captcha",
+            renames: &[("websiteKey", "sitekey")],
+            extra: &[],
+        },
+    ),
+    (
+        "FriendlyCaptchaTask",
+        Method {
+            name: "friendly_captcha",
+            renames: &[("websiteKey", "sitekey")],
+            extra: &[],
+        },
+    ),
+];
+
+/// Translates the JSON of a `createTask` task into the parameters of an
+/// `in.php` form, or returns `None` if the task type has no `in.php`
+/// equivalent
+pub fn params(task: &Value) -> Option<Map<String, Value>> {
+    let mut task = task.as_object()?.clone();
+
+    let task_type = task.remove("type")?;
+    let task_type = task_type.as_str()?;
+    let task_type = task_type.strip_suffix("Proxyless").unwrap_or(task_type);
+    let (_, method) = METHODS.iter().find(|(name, _)| *name == task_type)?;
+
+    let mut params = Map::new();
+    params.insert("method".into(), method.name.into());
+    for (name, value) in method.extra {
+        params.insert((*name).into(), (*value).into());
+    }
+
+    if let Some(proxy) = proxy(&mut task) {
+        params.extend(proxy);
+    }
+
+    // GeeTest v4 is a different method that takes the captcha id
+    if task.get("version").and_then(Value::as_u64) == Some(4) {
+        params.insert("method".into(), "geetest_v4".into());
+        if let Some(Value::Object(init)) = task.remove("initParameters") {
+            params.extend(init);
+        }
+    }
+
+    for (name, value) in task {
+        let name = match name.as_str() {
+            "websiteURL" | "websiteUrl" => "pageurl",
+            name => method
+                .renames
+                .iter()
+                .find(|(field, _)| *field == name)
+                .map_or(name, |(_, param)| param),
+        };
+
+        let value = match value {
+            Value::Bool(value) => u8::from(value).into(),
+            value => value,
+        };
+
+        params.insert(name.into(), value);
+    }
+
+    Some(params)
+}
+
+/// Takes the proxy fields out of `task`, joining them into the `proxy` and
+/// `proxytype` parameters
+fn proxy(task: &mut Map<String, Value>) -> Option<Map<String, Value>> {
+    let kind = task.remove("proxyType")?;
+    let address = task.remove("proxyAddress")?;
+    let port = task.remove("proxyPort")?;
+    let login = task.remove("proxyLogin");
+    let password = task.remove("proxyPassword");
+
+    let host = format!("{}:{}", address.as_str()?, port.as_str()?);
+    let proxy = match (login, password) {
+        (Some(login), Some(password)) => {
+            format!("{}:{}@{host}", login.as_str()?, password.as_str()?)
+        }
+        (Some(login), None) => format!("{}@{host}", login.as_str()?),
+        _ => host,
+    };
+
+    let mut params = Map::new();
+    params.insert("proxy".into(), proxy.into());
+    params.insert("proxytype".into(), kind.as_str()?.to_uppercase().into());
+    Some(params)
+}
+
+#[cfg(test)]
+mod test {
+    use std::borrow::Cow;
+
+    use serde_json::{from_str, json, to_value, Map, Value};
+
+    use super::{params, Request, Response, Status};
+
+    #[test]
+    fn request_serialization() {
+        let mut params = Map::new();
+        params.insert("method".into(), json!("base64"));
+
+        let request = Request {
+            key: "API_KEY",
+            soft_id: Some(4143),
+            json: 1,
+            #[cfg(feature = "callback")]
+            pingback: None,
+            params,
+        };
+
+        let expected = json!({
+            "key": "API_KEY",
+            "soft_id": 4143,
+            "json": 1,
+            "method": "base64",
+        });
+
+        assert_eq!(to_value(&request).unwrap(), expected);
+    }
+
+    #[test]
+    fn response_deserialization() {
+        let created = r#"{ "status": 1, "request": "2122988149" }"#;
+        let error = r#"{ "status": 0, "request": "ERROR_ZERO_BALANCE" }"#;
+
+        assert_eq!(
+            from_str::<Response<'_>>(created).unwrap(),
+            Response {
+                status: Status::Success,
+                request: Cow::Borrowed("2122988149"),
+                error_text: None,
+            }
+        );
+        assert_eq!(
+            from_str::<Response<'_>>(error).unwrap().status,
+            Status::Error
+        );
+    }
+
+    #[test]
+    fn translates_tasks() {
+        let task = json!({
+            "type": "RecaptchaV2Task",
+            "websiteURL": "https://example.com/",
+            "websiteKey": "SITE_KEY",
+            "isInvisible": true,
+            "proxyType": "http",
+            "proxyAddress": "1.2.3.4",
+            "proxyPort": "8080",
+            "proxyLogin": "user23",
+            "proxyPassword": "p4$$w0rd",
+        });
+
+        let expected = json!({
+            "method": "userrecaptcha",
+            "pageurl": "https://example.com/",
+            "googlekey": "SITE_KEY",
+            "invisible": 1,
+            "proxy": "user23:p4$$w0rd@1.2.3.4:8080",
+            "proxytype": "HTTP",
+        });
+
+        assert_eq!(Value::Object(params(&task).unwrap()), expected);
+    }
+
+    #[test]
+    fn translates_geetest_v4() {
+        let task = json!({
+            "type": "GeeTestTaskProxyless",
+            "websiteURL": "https://example.com/",
+            "version": 4,
+            "initParameters": { "captcha_id": "CAPTCHA_ID" },
+        });
+
+        let expected = json!({
+            "method": "geetest_v4",
+            "pageurl": "https://example.com/",
+            "version": 4,
+            "captcha_id": "CAPTCHA_ID",
+        });
+
+        assert_eq!(Value::Object(params(&task).unwrap()), expected);
+    }
+
+    #[test]
+    fn unsupported_tasks() {
+        assert!(params(&json!({ "type": "GridTask", "body": "..." })).is_none());
+        assert!(params(&json!({ "body": "..." })).is_none());
+    }
+}
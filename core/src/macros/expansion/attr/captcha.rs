@@ -89,9 +89,18 @@ fn expand_captcha_attr(args: TokenStream, item: &TokenStream) -> Result<TokenStr
         ));
     }
 
+    let task_type = generate_task_type(&input, attr.proxy.as_ref())?;
     let ident = &input.ident;
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
     let timeout = attr.timeout;
+    let poll_interval = (attr.poll_interval != 0).then(|| {
+        let poll_interval = attr.poll_interval;
+        quote! {
+            fn get_poll_interval(&self) -> std::time::Duration {
+                std::time::Duration::from_secs(#poll_interval)
+            }
+        }
+    });
     let solution = attr.solution();
 
     let Fields::Named(ref fields) = input.fields else {
@@ -115,6 +124,12 @@ fn expand_captcha_attr(args: TokenStream, item: &TokenStream) -> Result<TokenStr
             fn get_timeout(&self) -> std::time::Duration {
                 std::time::Duration::from_secs(#timeout)
             }
+
+            fn get_task_type(&self) -> &'static str {
+                #task_type
+            }
+
+            #poll_interval
         }
     })
 }
@@ -148,6 +163,32 @@ fn validate_task_type(
     }
 }
 
+/// The body of `get_task_type`, which picks the name of the task type from
+/// the proxy, or takes it from `#[serde(rename = "..")]` otherwise
+fn generate_task_type(input: &ItemStruct, proxy: Option<&ProxyTask>) -> Result<TokenStream> {
+    if let Some(proxy) = proxy {
+        let with_proxy = &*proxy.name_with_proxy;
+        let without_proxy = &*proxy.name_without_proxy;
+
+        return Ok(quote! {
+            match self.proxy {
+                ProxyTask::WithProxy(_) => #with_proxy,
+                ProxyTask::ProxyLess => #without_proxy,
+            }
+        });
+    }
+
+    let name = input
+        .attrs
+        .iter()
+        .filter(|x| x.path().is_ident("serde"))
+        .map(serde_type_name)
+        .try_fold(None, |acc, cur| Ok::<_, Error>(acc.or(cur?)))?
+        .unwrap_or_else(|| input.ident.to_string());
+
+    Ok(quote!(#name))
+}
+
 fn generate_proxy_mod(
     crate_rename: &Path,
     serde_rename: &Path,
@@ -214,9 +255,37 @@ fn is_valid_serde_tag(attr: &Attribute) -> Result<bool> {
                     )
                 })?;
             }
-            _ => continue,
+            _ => {}
         }
     }
 
     Ok(has_tag)
 }
+
+/// The value of `#[serde(rename = "..")]`, which is the name of the task
+/// type of the captchas tagged with `#[serde(tag = "type")]`
+fn serde_type_name(attr: &Attribute) -> Result<Option<String>> {
+    use Meta::NameValue as MNV;
+    use MetaNameValue as M;
+
+    let nested = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+
+    for meta in nested {
+        if let MNV(M {
+            ref path,
+            value:
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(ref literal),
+                    ..
+                }),
+            ..
+        }) = meta
+        {
+            if path.is_ident("rename") {
+                return Ok(Some(literal.value()));
+            }
+        }
+    }
+
+    Ok(None)
+}
@@ -325,7 +325,7 @@ impl<'a> Builder<'a> {
     }
 }
 
-impl<'a> ToTokens for Builder<'a> {
+impl ToTokens for Builder<'_> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let Self {
             ref ident,
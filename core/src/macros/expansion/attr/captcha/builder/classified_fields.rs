@@ -135,7 +135,7 @@ impl<'a> ClassifiedFields<'a> {
     }
 }
 
-impl<'a> ToTokens for ClassifiedFields<'a> {
+impl ToTokens for ClassifiedFields<'_> {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
         let comma = Comma {
             spans: [Span::call_site()],
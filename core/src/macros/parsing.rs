@@ -30,7 +30,7 @@ macro_rules! parse_attrs {
                             $(
                                 if $duplicate {
                                     return Err($input.error(
-                                        format!(r#"Duplicate attribute `{}`"#, $k)
+                                        format!(r"Duplicate attribute `{}`", $k)
                                     ));
                                 }
                             )?
@@ -66,7 +66,7 @@ macro_rules! parse_attrs {
                             $(
                                 if $duplicate {
                                     return Err($input.error(
-                                        format!(r#"Duplicate attribute `{}`"#, $k)
+                                        format!(r"Duplicate attribute `{}`", $k)
                                     ));
                                 }
                             )?
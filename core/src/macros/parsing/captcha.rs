@@ -21,6 +21,7 @@ pub struct Captcha {
     pub serde_rename: Option<Path>,
     pub proxy: Option<ProxyTask>,
     pub timeout: u64,
+    pub poll_interval: u64,
     pub solution: Option<Type>,
 }
 
@@ -30,7 +31,7 @@ impl Captcha {
     }
 }
 
-impl<'a> ContainerAttr<'a> for Captcha {
+impl ContainerAttr<'_> for Captcha {
     fn crate_rename(&self) -> Path {
         self.crate_rename
             .clone()
@@ -74,6 +75,15 @@ parse_attrs! {
 
             output.timeout = timeout;
         },
+        "poll_interval": output.poll_interval != 0 => {
+            let poll_interval = assign_int(&input)?.base10_parse()?;
+
+            if poll_interval == 0 {
+                return Err(input.error("`poll_interval` must not be 0"))
+            }
+
+            output.poll_interval = poll_interval;
+        },
         "solution": output.solution.is_some() => {
             output.solution = Some(assign(&input)?);
         },
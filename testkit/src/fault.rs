@@ -0,0 +1,37 @@
+use std::time::Duration;
+
+/// A failure [`crate::MockServer`] can inject into its answers, set up with
+/// [`crate::MockServer::inject`].
+///
+/// Requests that meet a fault are still recorded, and only
+/// [`Fault::Delay`] lets them reach the task they refer to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// Waits for the given duration and then answers normally
+    Delay(Duration),
+
+    /// Closes the connection without answering
+    Drop,
+
+    /// Answers with a `200` and a body that is cut off halfway through
+    MalformedJson,
+
+    /// Answers with the given HTTP status and an empty body
+    Status(u16),
+
+    /// Answers with the given 2captcha error code, e.g.
+    /// `ERROR_NO_SLOT_AVAILABLE`, without touching any task
+    ErrorCode(&'static str),
+}
+
+/// A fault that still has to be injected `remaining` more times into the
+/// answers of an API method
+#[derive(Debug)]
+pub struct Injection {
+    pub method: Box<str>,
+    pub fault: Fault,
+    pub remaining: usize,
+}
+
+/// What [`Fault::MalformedJson`] answers with
+pub const MALFORMED_JSON: &[u8] = br#"{ "errorId": 0, "status": "rea"#;
@@ -0,0 +1,45 @@
+//! An in-process stand-in for the 2captcha API, so `captcha_oxide` can be
+//! tested without an API key, a network connection or spending money.
+//!
+//! [`MockServer`] listens on a random local port and implements the
+//! `createTask`, `getTaskResult`, `getBalance`, `reportCorrect` and
+//! `reportIncorrect` methods, as well as their counterparts and the pingback
+//! actions of the legacy `in.php` and `res.php` endpoints. Every task it
+//! creates follows a [`TaskScript`] that decides what each poll for its
+//! result returns, and every request it receives is recorded so tests can
+//! assert on the JSON the client sent.
+//! Failures such as slow answers, dropped connections, malformed JSON and
+//! HTTP errors can be injected with [`MockServer::inject`].
+//!
+//! # Example
+//! ```
+//! use captcha_oxide_testkit::{MockServer, TaskScript};
+//! use serde_json::json;
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let server = MockServer::start().await;
+//!
+//! server.enqueue(
+//!     TaskScript::new()
+//!         .processing(2)
+//!         .ready(json!({ "text": "w68hp" })),
+//! );
+//!
+//! // Build a solver with `.api_url(server.url())`, solve a captcha with it
+//! // and check what it sent with `server.assert_last_task`
+//! assert_eq!(server.url().host_str(), Some("127.0.0.1"));
+//! # }
+//! ```
+
+#![deny(clippy::pedantic, clippy::nursery, clippy::mod_module_files)]
+#![forbid(unsafe_code)]
+
+mod fault;
+mod http;
+mod script;
+mod server;
+
+pub use fault::Fault;
+pub use script::TaskScript;
+pub use server::{MockServer, RecordedRequest};
@@ -0,0 +1,157 @@
+use serde_json::Value;
+
+/// Decides how [`crate::MockServer`] answers for a single task.
+///
+/// Each call to [`TaskScript::processing`], [`TaskScript::ready`] and
+/// [`TaskScript::error`] adds the answer to one or more polls for the task's
+/// result, in order. Once the script runs out, the last answer is repeated,
+/// and a script with no answers keeps the task processing forever.
+///
+/// # Example
+/// ```
+/// use captcha_oxide_testkit::TaskScript;
+/// use serde_json::json;
+///
+/// // Processing for the first two polls, solved on the third
+/// let solved = TaskScript::new()
+///     .processing(2)
+///     .ready(json!({ "token": "TOKEN" }))
+///     .cost("0.00145");
+///
+/// // Fails on the first poll
+/// let unsolvable = TaskScript::new().error("ERROR_CAPTCHA_UNSOLVABLE");
+///
+/// // Never created in the first place
+/// let rejected = TaskScript::rejected("ERROR_ZERO_BALANCE");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskScript {
+    pub(crate) rejection: Option<Box<str>>,
+    pub(crate) steps: Vec<Step>,
+    pub(crate) cost: Box<str>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    Processing,
+    Ready(Value),
+    Error(Box<str>),
+}
+
+impl Default for TaskScript {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TaskScript {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            rejection: None,
+            steps: Vec::new(),
+            cost: "0.00299".into(),
+        }
+    }
+
+    /// A task that `createTask` refuses to create, answering with the
+    /// given error code instead of a task id
+    #[must_use]
+    pub fn rejected(error_code: &str) -> Self {
+        Self {
+            rejection: Some(error_code.into()),
+            ..Self::new()
+        }
+    }
+
+    /// Answers the next `times` polls with `"status": "processing"`
+    #[must_use]
+    pub fn processing(mut self, times: usize) -> Self {
+        self.steps
+            .extend(std::iter::repeat(Step::Processing).take(times));
+        self
+    }
+
+    /// Answers the next poll with `"status": "ready"` and the given
+    /// `solution` object
+    #[must_use]
+    pub fn ready(mut self, solution: Value) -> Self {
+        self.steps.push(Step::Ready(solution));
+        self
+    }
+
+    /// Answers the next poll with the given error code
+    #[must_use]
+    pub fn error(mut self, error_code: &str) -> Self {
+        self.steps.push(Step::Error(error_code.into()));
+        self
+    }
+
+    /// The `cost` reported once the task is ready, which is also charged
+    /// from the server's balance. Defaults to `"0.00299"`
+    #[must_use]
+    pub fn cost(mut self, cost: &str) -> Self {
+        self.cost = cost.into();
+        self
+    }
+
+    /// The cost as a number, for charging it from the balance
+    pub(crate) fn price(&self) -> f64 {
+        self.cost.parse().unwrap_or_default()
+    }
+
+    /// The answer to the `poll`-th request for the task's result,
+    /// counting from 0
+    pub(crate) fn step(&self, poll: usize) -> &Step {
+        self.steps
+            .get(poll)
+            .or_else(|| self.steps.last())
+            .unwrap_or(&Step::Processing)
+    }
+}
+
+/// The `errorId` 2captcha sends along with each error code
+pub fn error_id(error_code: &str) -> u16 {
+    match error_code {
+        "ERROR_NO_SLOT_AVAILABLE" => 2,
+        "ERROR_ZERO_CAPTCHA_FILESIZE" => 3,
+        "ERROR_TOO_BIG_CAPTCHA_FILESIZE" => 4,
+        "ERROR_ZERO_BALANCE" => 10,
+        "ERROR_IP_NOT_ALLOWED" => 11,
+        "ERROR_CAPTCHA_UNSOLVABLE" => 12,
+        "ERROR_BAD_DUPLICATES" => 13,
+        "ERROR_NO_SUCH_METHOD" => 14,
+        "ERROR_IMAGE_TYPE_NOT_SUPPORTED" => 15,
+        "ERROR_NO_SUCH_CAPCHA_ID" => 16,
+        "ERROR_IP_BLOCKED" => 21,
+        "ERROR_TASK_ABSENT" => 22,
+        "ERROR_TASK_NOT_SUPPORTED" => 23,
+        "ERROR_RECAPTCHA_INVALID_SITEKEY" => 31,
+        "ERROR_ACCOUNT_SUSPENDED" => 55,
+        "ERROR_BAD_PARAMETERS" => 110,
+        "ERROR_BAD_IMGINSTRUCTIONS" => 115,
+        "ERROR_BAD_PROXY" => 130,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::{Step, TaskScript};
+
+    #[test]
+    fn repeats_the_last_step() {
+        let script = TaskScript::new().processing(1).ready(json!({}));
+
+        assert_eq!(script.step(0), &Step::Processing);
+        assert_eq!(script.step(1), &Step::Ready(json!({})));
+        assert_eq!(script.step(5), &Step::Ready(json!({})));
+    }
+
+    #[test]
+    fn empty_script_keeps_processing() {
+        assert_eq!(TaskScript::new().step(3), &Step::Processing);
+    }
+}
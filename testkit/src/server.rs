@@ -0,0 +1,591 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    io,
+    net::SocketAddr,
+    path::Path,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::{json, Value};
+use tokio::{
+    io::BufReader,
+    net::{TcpListener, TcpStream},
+    task::JoinHandle,
+    time::sleep,
+};
+use url::Url;
+
+use crate::{
+    fault::{Fault, Injection, MALFORMED_JSON},
+    http::{read_request, write_response},
+    script::{error_id, Step, TaskScript},
+};
+
+/// An HTTP server that behaves like the 2captcha API.
+///
+/// Tasks are created following the [`TaskScript`]s given to
+/// [`MockServer::enqueue`], in order. Tasks created once the queue is empty
+/// keep processing forever. The server shuts down when it is dropped.
+///
+/// Each task is charged its [`TaskScript::cost`] once it is ready, and
+/// `createTask` fails with `ERROR_ZERO_BALANCE` while the balance is lower
+/// than the cost of the next task, so a batch can run out of money halfway.
+/// Failures such as slow answers and dropped connections can be injected
+/// with [`MockServer::inject`].
+#[derive(Debug)]
+pub struct MockServer {
+    address: SocketAddr,
+    state: Arc<Mutex<State>>,
+    listener: JoinHandle<()>,
+}
+
+/// A request received by [`MockServer`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedRequest {
+    /// The path of the API method that was called, e.g. `/createTask`
+    pub path: String,
+
+    /// The parsed request body, or [`Value::Null`] if it was not valid JSON.
+    /// Requests to the legacy `.php` endpoints are parsed as forms, into an
+    /// object holding their fields as strings
+    pub body: Value,
+}
+
+#[derive(Debug)]
+struct State {
+    api_key: Option<Box<str>>,
+    balance: f64,
+    queue: VecDeque<TaskScript>,
+    tasks: HashMap<u64, Task>,
+    next_task_id: u64,
+    injections: Vec<Injection>,
+    requests: Vec<RecordedRequest>,
+    pingbacks: Vec<String>,
+}
+
+#[derive(Debug)]
+struct Task {
+    script: TaskScript,
+    polls: usize,
+    created_at: u64,
+    charged: bool,
+    report: Option<bool>,
+}
+
+impl MockServer {
+    /// Starts a server on a random port of the loopback interface. Must be
+    /// called from within a Tokio runtime
+    ///
+    /// # Panics
+    /// Panics if no local port can be bound
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Failed to bind the mock server to a local port");
+        let address = listener
+            .local_addr()
+            .expect("Failed to read the mock server's address");
+
+        let state = Arc::new(Mutex::new(State {
+            api_key: None,
+            balance: 10.0,
+            queue: VecDeque::new(),
+            tasks: HashMap::new(),
+            next_task_id: 1,
+            injections: Vec::new(),
+            requests: Vec::new(),
+            pingbacks: Vec::new(),
+        }));
+
+        let listener = tokio::spawn(accept(listener, Arc::clone(&state)));
+
+        Self {
+            address,
+            state,
+            listener,
+        }
+    }
+
+    /// The base URL of the server, to be given to
+    /// `CaptchaSolverBuilder::api_url` and `CaptchaSolverBuilder::legacy_api_url`
+    ///
+    /// # Panics
+    /// Never, as the address is always a valid URL
+    #[must_use]
+    pub fn url(&self) -> Url {
+        Url::parse(&format!("http://{}/", self.address)).expect("A socket address is a valid host")
+    }
+
+    /// Sets the script followed by the next task created by the server that
+    /// is not covered by a previously enqueued script
+    pub fn enqueue(&self, script: TaskScript) {
+        self.state().queue.push_back(script);
+    }
+
+    /// Makes the server reject every request whose `clientKey` is not
+    /// `api_key` with `ERROR_KEY_DOES_NOT_EXIST`. Any key is accepted by default
+    pub fn expect_api_key(&self, api_key: &str) {
+        self.state().api_key = Some(api_key.into());
+    }
+
+    /// Sets the balance returned by `getBalance`. Defaults to `10.0`
+    pub fn set_balance(&self, balance: f64) {
+        self.state().balance = balance;
+    }
+
+    /// The balance left after charging for every task solved so far
+    #[must_use]
+    pub fn balance(&self) -> f64 {
+        self.state().balance
+    }
+
+    /// Answers the next `times` requests to the given API method, e.g.
+    /// `getTaskResult`, with `fault`. Faults injected into the same method
+    /// are used up in the order they were injected
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use captcha_oxide_testkit::{Fault, MockServer};
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let server = MockServer::start().await;
+    ///
+    /// // A storm of throttling errors followed by an outage
+    /// server.inject("createTask", Fault::ErrorCode("ERROR_NO_SLOT_AVAILABLE"), 5);
+    /// server.inject("createTask", Fault::Status(503), 2);
+    ///
+    /// // A slow poll and a connection that drops
+    /// server.inject("getTaskResult", Fault::Delay(Duration::from_secs(1)), 1);
+    /// server.inject("getTaskResult", Fault::Drop, 1);
+    /// # }
+    /// ```
+    pub fn inject(&self, method: &str, fault: Fault, times: usize) {
+        self.state().injections.push(Injection {
+            method: method.into(),
+            fault,
+            remaining: times,
+        });
+    }
+
+    /// Every request received so far, in the order they arrived
+    #[must_use]
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state().requests.clone()
+    }
+
+    /// The `task` object of every `createTask` request received so far, in
+    /// the order they arrived
+    #[must_use]
+    pub fn tasks(&self) -> Vec<Value> {
+        self.state()
+            .requests
+            .iter()
+            .filter(|request| request.path == "/createTask")
+            .map(|request| request.body["task"].clone())
+            .collect()
+    }
+
+    /// The addresses currently registered for pingbacks through the legacy
+    /// `res.php` API
+    #[must_use]
+    pub fn pingbacks(&self) -> Vec<String> {
+        self.state().pingbacks.clone()
+    }
+
+    /// Whether the given task was reported as correct (`Some(true)`),
+    /// incorrect (`Some(false)`) or not reported at all (`None`)
+    #[must_use]
+    pub fn report(&self, task_id: u64) -> Option<bool> {
+        self.state()
+            .tasks
+            .get(&task_id)
+            .and_then(|task| task.report)
+    }
+
+    /// Asserts that the `task` object of the last `createTask` request
+    /// contains every field in `expected` with the same value. Fields not
+    /// in `expected`, such as large image bodies, are not compared
+    ///
+    /// # Panics
+    /// Panics if no task was created or if the last task does not match
+    #[track_caller]
+    pub fn assert_last_task(&self, expected: &Value) {
+        let tasks = self.tasks();
+        let actual = tasks.last().expect("No task was created");
+
+        assert!(
+            contains(actual, expected),
+            "The last task sent does not match\n  expected: {expected}\n  actual: {actual}"
+        );
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        lock(&self.state)
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.listener.abort();
+    }
+}
+
+async fn accept(listener: TcpListener, state: Arc<Mutex<State>>) {
+    while let Ok((stream, _)) = listener.accept().await {
+        tokio::spawn(serve(stream, Arc::clone(&state)));
+    }
+}
+
+async fn serve(stream: TcpStream, state: Arc<Mutex<State>>) -> io::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+
+    while let Some(request) = read_request(&mut reader).await? {
+        let body = parse_body(&request.path, &request.body);
+        let fault = lock(&state).take_fault(&request.path);
+
+        if let Some(Fault::Delay(delay)) = fault {
+            sleep(delay).await;
+        }
+
+        let Some((status, response)) = lock(&state).respond(&request.path, body, fault) else {
+            return Ok(());
+        };
+
+        write_response(&mut writer, status, &response).await?;
+    }
+
+    Ok(())
+}
+
+impl State {
+    /// Records the request and builds the status and body of its answer,
+    /// or returns `None` if the connection should be dropped instead
+    fn respond(&mut self, path: &str, body: Value, fault: Option<Fault>) -> Option<(u16, Vec<u8>)> {
+        let response = match fault {
+            Some(Fault::Drop) => None,
+            Some(Fault::MalformedJson) => Some((200, MALFORMED_JSON.to_vec())),
+            Some(Fault::Status(status)) => Some((status, Vec::new())),
+            Some(Fault::ErrorCode(code)) => Some((200, error(code).to_string().into_bytes())),
+            Some(Fault::Delay(_)) | None => {
+                Some((200, self.dispatch(path, &body).to_string().into_bytes()))
+            }
+        };
+
+        self.requests.push(RecordedRequest {
+            path: path.to_owned(),
+            body,
+        });
+
+        response
+    }
+
+    /// Uses up the first fault still injected into the method at `path`
+    fn take_fault(&mut self, path: &str) -> Option<Fault> {
+        let method = path.trim_start_matches('/');
+        let injection = self
+            .injections
+            .iter_mut()
+            .find(|injection| &*injection.method == method && injection.remaining > 0)?;
+
+        injection.remaining -= 1;
+        Some(injection.fault)
+    }
+
+    fn dispatch(&mut self, path: &str, body: &Value) -> Value {
+        if is_legacy(path) {
+            return self.legacy(path, body);
+        }
+
+        if let Some(api_key) = &self.api_key {
+            if body["clientKey"].as_str() != Some(api_key) {
+                return error("ERROR_KEY_DOES_NOT_EXIST");
+            }
+        }
+
+        match path {
+            "/createTask" => self.create_task(body),
+            "/getTaskResult" => self.get_task_result(body),
+            "/getBalance" => json!({ "errorId": 0, "balance": self.balance }),
+            "/reportCorrect" => self.report(body, true),
+            "/reportIncorrect" => self.report(body, false),
+            _ => error("ERROR_NO_SUCH_METHOD"),
+        }
+    }
+
+    fn create_task(&mut self, body: &Value) -> Value {
+        if !body["task"].is_object() {
+            return error("ERROR_TASK_ABSENT");
+        }
+
+        match self.create() {
+            Ok(task_id) => json!({ "errorId": 0, "taskId": task_id }),
+            Err(code) => error(&code),
+        }
+    }
+
+    /// Creates a task following the next script, returning its id or the
+    /// error code it was rejected with
+    fn create(&mut self) -> Result<u64, Box<str>> {
+        let script = self.queue.pop_front().unwrap_or_default();
+        if let Some(code) = &script.rejection {
+            return Err(code.clone());
+        }
+
+        if self.balance < script.price() {
+            return Err("ERROR_ZERO_BALANCE".into());
+        }
+
+        let task_id = self.next_task_id;
+        self.next_task_id += 1;
+
+        self.tasks.insert(
+            task_id,
+            Task {
+                script,
+                polls: 0,
+                created_at: now(),
+                charged: false,
+                report: None,
+            },
+        );
+
+        Ok(task_id)
+    }
+
+    fn get_task_result(&mut self, body: &Value) -> Value {
+        let Some(task_id) = body["taskId"].as_u64() else {
+            return error("ERROR_NO_SUCH_CAPCHA_ID");
+        };
+
+        match self.poll(task_id) {
+            Ok(None) => json!({ "errorId": 0, "status": "processing" }),
+            Ok(Some((solution, task))) => json!({
+                "errorId": 0,
+                "status": "ready",
+                "solution": solution,
+                "cost": task.script.cost,
+                "ip": "127.0.0.1",
+                "createTime": task.created_at,
+                "endTime": now(),
+                "solveCount": 1,
+            }),
+            Err(code) => error(&code),
+        }
+    }
+
+    /// Takes the next step of the given task's script, charging for it once
+    /// it is ready. Returns its solution, `None` while it is processing or
+    /// the error code it failed with
+    fn poll(&mut self, task_id: u64) -> Result<Option<(Value, &Task)>, Box<str>> {
+        let Some(task) = self.tasks.get_mut(&task_id) else {
+            return Err("ERROR_NO_SUCH_CAPCHA_ID".into());
+        };
+
+        let step = task.script.step(task.polls).clone();
+        task.polls += 1;
+
+        match step {
+            Step::Processing => Ok(None),
+            Step::Ready(solution) => {
+                if !task.charged {
+                    task.charged = true;
+                    self.balance -= task.script.price();
+                }
+
+                Ok(Some((solution, &*task)))
+            }
+            Step::Error(code) => Err(code),
+        }
+    }
+
+    fn report(&mut self, body: &Value, correct: bool) -> Value {
+        let Some(task) = self.task(body) else {
+            return error("ERROR_NO_SUCH_CAPCHA_ID");
+        };
+
+        task.report = Some(correct);
+
+        json!({ "errorId": 0, "status": "success" })
+    }
+
+    /// Answers requests to the legacy API, which take a form and respond
+    /// with a `status` and a `request` field
+    fn legacy(&mut self, path: &str, body: &Value) -> Value {
+        if let Some(api_key) = &self.api_key {
+            if body["key"].as_str() != Some(api_key) {
+                return legacy_error("ERROR_WRONG_USER_KEY");
+            }
+        }
+
+        match (path, body["action"].as_str()) {
+            ("/in.php", _) => {
+                if body["method"].as_str().is_none() {
+                    return legacy_error("ERROR_BAD_PARAMETERS");
+                }
+
+                match self.create() {
+                    Ok(task_id) => json!({ "status": 1, "request": task_id.to_string() }),
+                    Err(code) => legacy_error(&code),
+                }
+            }
+            ("/res.php", Some("get" | "get2")) => {
+                let Some(task_id) = legacy_task_id(body) else {
+                    return legacy_error("ERROR_WRONG_ID_FORMAT");
+                };
+
+                match self.poll(task_id) {
+                    Ok(None) => json!({ "status": 0, "request": "CAPCHA_NOT_READY" }),
+                    Ok(Some((solution, task))) => {
+                        json!({ "status": 1, "request": answer(solution), "price": task.script.cost })
+                    }
+                    Err(code) => legacy_error(&code),
+                }
+            }
+            ("/res.php", Some("getbalance")) => {
+                json!({ "status": 1, "request": self.balance.to_string() })
+            }
+            ("/res.php", Some(action @ ("reportgood" | "reportbad"))) => {
+                let Some(task) = legacy_task_id(body).and_then(|id| self.tasks.get_mut(&id)) else {
+                    return legacy_error("ERROR_WRONG_CAPTCHA_ID");
+                };
+
+                task.report = Some(action == "reportgood");
+
+                json!({ "status": 1, "request": "OK_REPORT_RECORDED" })
+            }
+            ("/res.php", Some("add_pingback")) => {
+                let Some(addr) = body["addr"].as_str() else {
+                    return legacy_error("ERROR_BAD_PARAMETERS");
+                };
+
+                if !self.pingbacks.iter().any(|pingback| pingback == addr) {
+                    self.pingbacks.push(addr.to_owned());
+                }
+
+                json!({ "status": 1, "request": "OK" })
+            }
+            ("/res.php", Some("get_pingback")) => {
+                json!({ "status": 1, "request": self.pingbacks })
+            }
+            ("/res.php", Some("del_pingback")) => {
+                match body["addr"].as_str() {
+                    Some("all") => self.pingbacks.clear(),
+                    Some(addr) => self.pingbacks.retain(|pingback| pingback != addr),
+                    None => return legacy_error("ERROR_BAD_PARAMETERS"),
+                }
+
+                json!({ "status": 1, "request": "OK" })
+            }
+            _ => legacy_error("ERROR_NO_SUCH_METHOD"),
+        }
+    }
+
+    fn task(&mut self, body: &Value) -> Option<&mut Task> {
+        body["taskId"]
+            .as_u64()
+            .and_then(|task_id| self.tasks.get_mut(&task_id))
+    }
+}
+
+fn error(code: &str) -> Value {
+    json!({
+        "errorId": error_id(code),
+        "errorCode": code,
+        "errorDescription": format!("Mock server error: {code}"),
+    })
+}
+
+fn legacy_error(code: &str) -> Value {
+    json!({
+        "status": 0,
+        "request": code,
+        "error_text": format!("Mock server error: {code}"),
+    })
+}
+
+/// The `id` field of a legacy request, which is sent as a string
+fn legacy_task_id(body: &Value) -> Option<u64> {
+    body["id"].as_str()?.parse().ok()
+}
+
+/// The legacy API answers with the solution itself when it is a single
+/// value, e.g. the text of an image captcha, and with an object otherwise
+fn answer(solution: Value) -> Value {
+    match solution {
+        Value::Object(fields) if fields.len() == 1 => fields
+            .into_iter()
+            .next()
+            .map_or(Value::Null, |(_, value)| value),
+        solution => solution,
+    }
+}
+
+/// Whether `path` is one of the legacy API's `.php` endpoints
+fn is_legacy(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .is_some_and(|extension| extension == "php")
+}
+
+/// Parses a JSON body, or a form for the legacy `.php` endpoints
+fn parse_body(path: &str, body: &[u8]) -> Value {
+    if is_legacy(path) {
+        return Value::Object(
+            url::form_urlencoded::parse(body)
+                .map(|(key, value)| (key.into_owned(), Value::String(value.into_owned())))
+                .collect(),
+        );
+    }
+
+    serde_json::from_slice(body).unwrap_or(Value::Null)
+}
+
+/// Whether `actual` has every field of `expected`, compared recursively
+/// for objects and exactly for any other value
+fn contains(actual: &Value, expected: &Value) -> bool {
+    match (actual, expected) {
+        (Value::Object(actual), Value::Object(expected)) => expected.iter().all(|(key, value)| {
+            actual
+                .get(key)
+                .is_some_and(|actual| contains(actual, value))
+        }),
+        _ => actual == expected,
+    }
+}
+
+/// A panic while the lock is held can only come from a failed assertion in
+/// a test, so the state is still consistent
+fn lock(state: &Mutex<State>) -> MutexGuard<'_, State> {
+    state
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs())
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::contains;
+
+    #[test]
+    fn partial_match() {
+        let actual = json!({ "type": "ImageToTextTask", "body": "...", "case": true });
+
+        assert!(contains(&actual, &json!({ "type": "ImageToTextTask" })));
+        assert!(!contains(
+            &actual,
+            &json!({ "type": "ImageToTextTask", "case": false })
+        ));
+        assert!(!contains(&actual, &json!({ "numeric": 1 })));
+    }
+}